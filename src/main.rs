@@ -1,9 +1,17 @@
+// Phi is meant to be reused by other games, so that it offers more than this
+// one needs, e.g. `Audio::crossfade_music` or `Bindings::bind`.
+#[allow(dead_code)]
 mod phi;
 mod views;
 
 
 fn main() {
-    phi::spawn("ArcadeRS Shooter", |phi| {
-        Box::new(views::main_menu::MainMenuView::new(phi))
+    let result = phi::spawn("ArcadeRS Shooter", |phi| {
+        Ok(Box::new(views::main_menu::MainMenuView::new(phi)?))
     });
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
 }
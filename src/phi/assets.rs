@@ -0,0 +1,84 @@
+use crate::phi::error::{PhiError, Result};
+use crate::phi::gfx::Sprite;
+use sdl2::image::LoadTexture;
+use sdl2::mixer::{Chunk, Music};
+use sdl2::render::WindowCanvas;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+
+/// Loads the textures, sounds and music of the game, keyed by their path, and
+/// keeps them around so that every file is only ever read once. Assets are
+/// handed out as cheap handles to the shared data: sprites already share
+/// their texture, while sounds and music are reference-counted.
+pub struct Assets<'r> {
+    /// Whether the audio device could be opened. If not, the game runs
+    /// silently, and there are no sounds nor music to load.
+    audio: bool,
+
+    textures: HashMap<String, Sprite<'r>>,
+    sounds: HashMap<String, Rc<Chunk>>,
+    music: HashMap<String, Rc<Music<'static>>>,
+}
+
+impl<'r> Assets<'r> {
+    pub fn new(audio: bool) -> Assets<'r> {
+        Assets {
+            audio: audio,
+            textures: HashMap::new(),
+            sounds: HashMap::new(),
+            music: HashMap::new(),
+        }
+    }
+
+    /// The image at `path`, as a sprite covering all of it.
+    pub fn texture(&mut self, renderer: &WindowCanvas, path: &str) -> Result<Sprite<'r>> {
+        if let Some(sprite) = self.textures.get(path) {
+            return Ok(sprite.clone());
+        }
+
+        let sprite = renderer.texture_creator().load_texture(Path::new(path))
+            .map(Sprite::new)
+            .map_err(|reason| PhiError::asset(path, reason))?;
+
+        self.textures.insert(path.to_string(), sprite.clone());
+        Ok(sprite)
+    }
+
+    /// The sound effect at `path`, or `None` if the game runs without audio.
+    pub fn sound(&mut self, path: &str) -> Result<Option<Rc<Chunk>>> {
+        if !self.audio {
+            return Ok(None);
+        }
+
+        if let Some(sound) = self.sounds.get(path) {
+            return Ok(Some(sound.clone()));
+        }
+
+        let sound = Chunk::from_file(Path::new(path))
+            .map(Rc::new)
+            .map_err(|reason| PhiError::asset(path, reason))?;
+
+        self.sounds.insert(path.to_string(), sound.clone());
+        Ok(Some(sound))
+    }
+
+    /// The music at `path`, or `None` if the game runs without audio.
+    pub fn music(&mut self, path: &str) -> Result<Option<Rc<Music<'static>>>> {
+        if !self.audio {
+            return Ok(None);
+        }
+
+        if let Some(music) = self.music.get(path) {
+            return Ok(Some(music.clone()));
+        }
+
+        let music = Music::from_file(Path::new(path))
+            .map(Rc::new)
+            .map_err(|reason| PhiError::asset(path, reason))?;
+
+        self.music.insert(path.to_string(), music.clone());
+        Ok(Some(music))
+    }
+}
@@ -0,0 +1,480 @@
+use crate::phi::{LOGICAL_HEIGHT, LOGICAL_WIDTH};
+use crate::phi::config::AudioConfig;
+use sdl2::mixer::{self, Channel, Chunk, Music, MAX_VOLUME};
+use std::rc::Rc;
+
+
+/// How much quieter, on SDL_mixer's scale from 0 to 255, the sounds in the
+/// corners of the screen are than those at its center. We keep them well
+/// audible, as everything on the screen matters to the player.
+const MAX_ATTENUATION: f64 = 140.0;
+
+/// How a sound effect competes with the others for the limited voices.
+#[derive(Clone, Copy, Debug)]
+pub struct SoundSettings {
+    /// Sounds may only take the voice of sounds whose priority is at most
+    /// their own.
+    pub priority: u8,
+    /// How many times the sound may play at once. Past that, its oldest
+    /// instance is cut short to play the new one.
+    pub max_instances: usize,
+}
+
+impl Default for SoundSettings {
+    fn default() -> SoundSettings {
+        SoundSettings {
+            priority: 0,
+            max_instances: 4,
+        }
+    }
+}
+
+/// What plays on a mixer channel.
+#[derive(Clone, Copy, Debug)]
+pub struct Voice {
+    /// Tells sounds apart. Two voices play the same sound iff they have the
+    /// same id.
+    sound: usize,
+    pub priority: u8,
+    /// When the voice started, as counted by `Audio::started`. The lower, the
+    /// older.
+    started: u64,
+    /// How much the voice is attenuated, on SDL_mixer's scale from 0 to 255.
+    pub attenuation: u8,
+}
+
+/// Opens the audio device as described by `config`, or returns `None` if
+/// there is none we can use, in which case the game runs silently rather than
+/// not at all.
+pub(super) fn open(config: &AudioConfig) -> Option<mixer::Sdl2MixerContext> {
+    let opened = mixer::init(mixer::InitFlag::OGG)
+        .and_then(|context| {
+            mixer::open_audio(config.frequency, config.format, config.channels, config.chunk_size)?;
+            Ok(context)
+        });
+
+    match opened {
+        Ok(context) => Some(context),
+
+        Err(err) => {
+            println!("Could not open the audio device, running without sound: {}", err);
+            None
+        },
+    }
+}
+
+/// Plays the music and the sound effects of the game. Views should go through
+/// it rather than through `sdl2::mixer`, so that the volume settings apply.
+///
+/// Sounds go through two buses, one for the music and one for the sound
+/// effects, each with its own volume. Both are then scaled by the master
+/// volume, which can also be muted altogether. Volumes are in [0, 1].
+///
+/// If the audio device could not be opened, every method does nothing.
+pub struct Audio {
+    enabled: bool,
+
+    /// What every mixer channel is playing, if anything. There is one voice
+    /// per channel, which is how many sound effects may play at once. Past
+    /// that, new sounds take the place of less important ones, or are not
+    /// played at all.
+    voices: Vec<Option<Voice>>,
+    /// How many sounds have been played so far.
+    started: u64,
+
+    master_volume: f64,
+    music_volume: f64,
+    sfx_volume: f64,
+    muted: bool,
+
+    //? SDL_mixer does not own the music it plays, so we have to keep it alive
+    //? for as long as it is playing.
+    music: Option<Rc<Music<'static>>>,
+    /// The music to start once the current one has faded out, along with how
+    /// long it should take to fade in, in seconds.
+    next_music: Option<(Rc<Music<'static>>, f64)>,
+}
+
+impl Audio {
+    pub(super) fn new(enabled: bool, voices: usize) -> Audio {
+        if enabled {
+            mixer::allocate_channels(voices as i32);
+        }
+
+        let mut audio = Audio {
+            enabled: enabled,
+
+            voices: vec![None; voices],
+            started: 0,
+
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            muted: false,
+
+            music: None,
+            next_music: None,
+        };
+
+        audio.apply_volumes();
+        audio
+    }
+
+    /// Whether the game actually makes sounds, i.e. the audio device could be
+    /// opened.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn master_volume(&self) -> f64 { self.master_volume }
+    pub fn music_volume(&self) -> f64 { self.music_volume }
+    pub fn sfx_volume(&self) -> f64 { self.sfx_volume }
+    pub fn is_muted(&self) -> bool { self.muted }
+
+    pub fn set_master_volume(&mut self, volume: f64) {
+        self.master_volume = volume.max(0.0).min(1.0);
+        self.apply_volumes();
+    }
+
+    pub fn set_music_volume(&mut self, volume: f64) {
+        self.music_volume = volume.max(0.0).min(1.0);
+        self.apply_volumes();
+    }
+
+    pub fn set_sfx_volume(&mut self, volume: f64) {
+        self.sfx_volume = volume.max(0.0).min(1.0);
+        self.apply_volumes();
+    }
+
+    /// Silences everything without forgetting the volumes.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.apply_volumes();
+    }
+
+    /// Converts a volume of the given bus to SDL_mixer's scale.
+    fn mixer_volume(&self, bus_volume: f64) -> i32 {
+        if self.muted {
+            0
+        } else {
+            (bus_volume * self.master_volume * MAX_VOLUME as f64).round() as i32
+        }
+    }
+
+    fn apply_volumes(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        Music::set_volume(self.mixer_volume(self.music_volume));
+        Channel::all().set_volume(self.mixer_volume(self.sfx_volume));
+    }
+
+    /// Plays a sound effect once, centered.
+    pub fn play_sound(&mut self, sound: &Chunk, settings: SoundSettings) {
+        if let Some(channel) = self.play(sound, settings, 0) {
+            // The channel may have last played a positional sound.
+            let _ = channel.set_panning(255, 255);
+            let _ = channel.set_distance(0);
+        }
+    }
+
+    /// Plays a sound effect once as if it came from `pos`, in logical
+    /// coordinates. It is panned towards the side of the screen where it
+    /// happens, and gets quieter the farther it is from the center.
+    pub fn play_sound_at(&mut self, sound: &Chunk, pos: (f64, f64), settings: SoundSettings) {
+        let attenuation = attenuation(pos);
+
+        if let Some(channel) = self.play(sound, settings, attenuation) {
+            let (left, right) = panning(pos.0 / LOGICAL_WIDTH as f64);
+            let _ = channel.set_panning(left, right);
+            let _ = channel.set_distance(attenuation);
+        }
+    }
+
+    /// Plays a sound effect on a voice chosen by `pick_voice`, and returns its
+    /// channel, or `None` if the sound was dropped.
+    fn play(&mut self, sound: &Chunk, settings: SoundSettings, attenuation: u8) -> Option<Channel> {
+        if !self.enabled {
+            return None;
+        }
+
+        let voice = Voice {
+            sound: sound.raw as usize,
+            priority: settings.priority,
+            started: self.started,
+            attenuation: attenuation,
+        };
+
+        let i = self.pick_voice(voice, settings.max_instances)?;
+        let channel = Channel(i as i32);
+
+        // Cut short whatever the voice was playing.
+        channel.halt();
+
+        match channel.play(sound, 0) {
+            Ok(channel) => {
+                self.started += 1;
+                self.voices[i] = Some(voice);
+                Some(channel)
+            },
+
+            Err(err) => {
+                println!("Could not play a sound: {}", err);
+                None
+            },
+        }
+    }
+
+    /// Chooses the voice on which to play a new sound, see `pick`.
+    fn pick_voice(&mut self, voice: Voice, max_instances: usize) -> Option<usize> {
+        self.forget_finished_voices();
+        pick(&self.voices, voice, max_instances)
+    }
+
+    /// Frees the voices whose sound has ended.
+    fn forget_finished_voices(&mut self) {
+        for (i, voice) in self.voices.iter_mut().enumerate() {
+            if voice.is_some() && !Channel(i as i32).is_playing() {
+                *voice = None;
+            }
+        }
+    }
+
+    /// The voices which are currently playing, along with their channel, for
+    /// debugging purposes.
+    pub fn active_voices(&mut self) -> Vec<(usize, Voice)> {
+        if self.enabled {
+            self.forget_finished_voices();
+        }
+
+        self.voices.iter().enumerate()
+            .filter_map(|(i, voice)| voice.map(|voice| (i, voice)))
+            .collect()
+    }
+
+    /// How many sound effects may play at once.
+    pub fn voice_capacity(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Starts playing `music` in a loop, fading it in over `fade_in` seconds.
+    /// Whatever music was playing is stopped.
+    pub fn play_music(&mut self, music: Rc<Music<'static>>, fade_in: f64) {
+        if !self.enabled {
+            return;
+        }
+
+        self.next_music = None;
+
+        if let Err(err) = music.fade_in(-1, to_ms(fade_in)) {
+            // The game is still playable without its music.
+            println!("Could not play the music: {}", err);
+        }
+
+        self.music = Some(music);
+    }
+
+    /// Fades the current music out over `duration` seconds, after which it
+    /// stops.
+    pub fn fade_out_music(&mut self, duration: f64) {
+        if !self.enabled {
+            return;
+        }
+
+        self.next_music = None;
+
+        if to_ms(duration) == 0 || Music::fade_out(to_ms(duration)).is_err() {
+            Music::halt();
+        }
+    }
+
+    /// Replaces the current music by `music` over `duration` seconds. SDL_mixer
+    /// only plays one piece of music at a time, so that the current one fades
+    /// out during the first half, and the new one fades in during the second.
+    pub fn crossfade_music(&mut self, music: Rc<Music<'static>>, duration: f64) {
+        if !self.enabled {
+            return;
+        }
+
+        if !Music::is_playing() {
+            self.play_music(music, duration / 2.0);
+            return;
+        }
+
+        self.fade_out_music(duration / 2.0);
+        self.next_music = Some((music, duration / 2.0));
+    }
+
+    /// Called on every frame to start the music which was waiting for the
+    /// previous one to fade out.
+    pub(super) fn update(&mut self) {
+        if self.next_music.is_some() && !Music::is_playing() {
+            let (music, fade_in) = self.next_music.take().unwrap();
+            self.play_music(music, fade_in);
+        }
+    }
+
+    /// Freezes the music and the sound effects, e.g. while the game is paused.
+    pub fn pause(&mut self) {
+        if self.enabled {
+            Music::pause();
+            Channel::all().pause();
+        }
+    }
+
+    /// Resumes whatever `pause` froze.
+    pub fn resume(&mut self) {
+        if self.enabled {
+            Music::resume();
+            Channel::all().resume();
+        }
+    }
+
+    /// Stops the music and every sound effect at once.
+    pub fn stop(&mut self) {
+        if self.enabled {
+            self.next_music = None;
+            Music::halt();
+            Channel::all().halt();
+            self.voices = vec![None; self.voices.len()];
+        }
+    }
+}
+
+/// Chooses the voice on which to play a new sound, given what the `voices`
+/// are playing. This is, in order:
+///
+/// * the oldest instance of the sound, if it already plays as many times as
+///   it may;
+/// * a voice which plays nothing;
+/// * among the voices whose priority is at most that of the sound, the one
+///   with the lowest priority, then the quietest, then the oldest.
+///
+/// Returns `None` if every voice plays something more important.
+fn pick(voices: &[Option<Voice>], new: Voice, max_instances: usize) -> Option<usize> {
+    let instances: Vec<_> = (0..voices.len())
+        .filter(|&i| voices[i].map_or(false, |other| other.sound == new.sound))
+        .collect();
+
+    if instances.len() >= max_instances.max(1) {
+        return instances.into_iter()
+            .min_by_key(|&i| voices[i].unwrap().started);
+    }
+
+    if let Some(i) = voices.iter().position(|other| other.is_none()) {
+        return Some(i);
+    }
+
+    (0..voices.len())
+        .filter(|&i| voices[i].unwrap().priority <= new.priority)
+        .min_by_key(|&i| {
+            let other = voices[i].unwrap();
+            (other.priority, 255 - other.attenuation, other.started)
+        })
+}
+
+fn to_ms(seconds: f64) -> i32 {
+    (seconds.max(0.0) * 1_000.0) as i32
+}
+
+/// The volume of the left and right speakers, from 0 to 255, for a sound
+/// happening `x` of the way across the screen, from 0 on its left edge to 1 on
+/// its right one. Sounds in the middle play at full volume on both sides.
+fn panning(x: f64) -> (u8, u8) {
+    let x = x.max(0.0).min(1.0);
+    let left = (2.0 * (1.0 - x)).min(1.0);
+    let right = (2.0 * x).min(1.0);
+
+    ((left * 255.0).round() as u8, (right * 255.0).round() as u8)
+}
+
+/// How much to attenuate a sound happening at `pos`, from 0 at the center of
+/// the screen to `MAX_ATTENUATION` in its corners.
+fn attenuation(pos: (f64, f64)) -> u8 {
+    let (half_w, half_h) = (LOGICAL_WIDTH as f64 / 2.0, LOGICAL_HEIGHT as f64 / 2.0);
+    let (dx, dy) = (pos.0 - half_w, pos.1 - half_h);
+    let distance = (dx * dx + dy * dy).sqrt() / (half_w * half_w + half_h * half_h).sqrt();
+
+    (distance.min(1.0) * MAX_ATTENUATION).round() as u8
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice(sound: usize, priority: u8, started: u64, attenuation: u8) -> Voice {
+        Voice { sound, priority, started, attenuation }
+    }
+
+    #[test]
+    fn pick_cuts_the_oldest_instance_past_the_limit() {
+        let voices = [
+            Some(voice(1, 0, 5, 0)),
+            None,
+            Some(voice(1, 0, 3, 0)),
+            Some(voice(2, 0, 1, 0)),
+        ];
+
+        assert_eq!(pick(&voices, voice(1, 0, 9, 0), 2), Some(2));
+        assert_eq!(pick(&voices, voice(1, 0, 9, 0), 3), Some(1));
+        // A sound always gets at least one voice.
+        assert_eq!(pick(&voices, voice(2, 0, 9, 0), 0), Some(3));
+    }
+
+    #[test]
+    fn pick_prefers_a_free_voice() {
+        let voices = [Some(voice(1, 0, 0, 0)), None, None];
+        assert_eq!(pick(&voices, voice(2, 0, 9, 0), 4), Some(1));
+    }
+
+    #[test]
+    fn pick_steals_the_least_important_voice() {
+        let voices = [
+            Some(voice(1, 2, 0, 0)),
+            Some(voice(2, 1, 4, 0)),
+            Some(voice(3, 1, 2, 100)),
+            Some(voice(4, 1, 1, 100)),
+        ];
+
+        // The lowest priority, then the quietest, then the oldest.
+        assert_eq!(pick(&voices, voice(5, 1, 9, 0), 4), Some(3));
+
+        // Sounds cannot take the voice of more important ones.
+        assert_eq!(pick(&voices, voice(5, 0, 9, 0), 4), None);
+        assert_eq!(pick(&[Some(voice(1, 2, 0, 0))], voice(5, 1, 9, 0), 4), None);
+    }
+
+    #[test]
+    fn panning_follows_the_sound_across_the_screen() {
+        assert_eq!(panning(0.0), (255, 0));
+        assert_eq!(panning(0.25), (255, 128));
+        assert_eq!(panning(0.5), (255, 255));
+        assert_eq!(panning(0.75), (128, 255));
+        assert_eq!(panning(1.0), (0, 255));
+
+        // Sounds off the screen play as if they were on its edge.
+        assert_eq!(panning(-1.0), (255, 0));
+        assert_eq!(panning(2.0), (0, 255));
+    }
+
+    #[test]
+    fn attenuation_grows_away_from_the_center() {
+        let (w, h) = (LOGICAL_WIDTH as f64, LOGICAL_HEIGHT as f64);
+
+        assert_eq!(attenuation((w / 2.0, h / 2.0)), 0);
+
+        // The distance to the edges is relative to that to the corners.
+        assert_eq!(attenuation((0.0, h / 2.0)), 112);
+        assert_eq!(attenuation((w, h / 2.0)), 112);
+        assert_eq!(attenuation((w / 2.0, 0.0)), 84);
+        assert_eq!(attenuation((w / 2.0, h)), 84);
+
+        for &corner in &[(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)] {
+            assert_eq!(attenuation(corner), MAX_ATTENUATION as u8);
+        }
+
+        assert_eq!(attenuation((-w, 2.0 * h)), MAX_ATTENUATION as u8);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::phi::Action;
+use sdl2::controller::Button;
+use sdl2::keyboard::Keycode;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+
+/// Maps the physical keys and controller buttons to the actions they
+/// trigger. A key may trigger several actions, e.g. `Space` both fires and
+/// confirms menu entries, and an action may be triggered by several keys.
+///
+/// Bindings are stored in a text file with one line per action:
+///
+///     # Comments start with a hash.
+///     MoveUp = Up, W, Pad:dpup
+///     Fire = Space, Pad:a
+///
+/// Keys are named as SDL names them, and controller buttons as they appear in
+/// SDL's controller mappings, prefixed by `Pad:`. Actions which are not listed
+/// keep their default keys and buttons.
+#[derive(Clone)]
+pub struct Bindings {
+    keys: HashMap<Action, Vec<Keycode>>,
+    buttons: HashMap<Action, Vec<Button>>,
+}
+
+impl Bindings {
+    /// The bindings defined alongside the actions in `struct_events!`.
+    pub fn defaults() -> Bindings {
+        Bindings {
+            keys: Action::ALL.iter()
+                .map(|&action| (action, action.default_keys()))
+                .collect(),
+            buttons: Action::ALL.iter()
+                .map(|&action| (action, action.default_buttons()))
+                .collect(),
+        }
+    }
+
+    /// Reads the bindings from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Bindings> {
+        Bindings::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Reads the bindings from the content of a bindings file.
+    pub fn parse(source: &str) -> io::Result<Bindings> {
+        let mut bindings = Bindings::defaults();
+
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |message: String| {
+                io::Error::new(io::ErrorKind::InvalidData,
+                    format!("line {}: {}", i + 1, message))
+            };
+
+            let mut sides = line.splitn(2, '=');
+            let name = sides.next().unwrap().trim();
+            let inputs = sides.next()
+                .ok_or_else(|| invalid("expected `Action = Key, Key, ...`".to_string()))?;
+
+            let action = Action::from_name(name)
+                .ok_or_else(|| invalid(format!("unknown action `{}`", name)))?;
+
+            let mut keys = vec![];
+            let mut buttons = vec![];
+
+            for input in inputs.split(',').map(|input| input.trim()) {
+                if input.is_empty() {
+                    continue;
+                } else if input.starts_with("Pad:") {
+                    buttons.push(Button::from_string(&input[4..])
+                        .ok_or_else(|| invalid(format!("unknown button `{}`", input)))?);
+                } else {
+                    keys.push(Keycode::from_name(input)
+                        .ok_or_else(|| invalid(format!("unknown key `{}`", input)))?);
+                }
+            }
+
+            bindings.keys.insert(action, keys);
+            bindings.buttons.insert(action, buttons);
+        }
+
+        Ok(bindings)
+    }
+
+    /// Reads the bindings from the file at `path`, falling back to the
+    /// default ones if it is missing or malformed.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Bindings {
+        match Bindings::load(&path) {
+            Ok(bindings) => bindings,
+
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound =>
+                Bindings::defaults(),
+
+            Err(err) => {
+                println!("Could not read the bindings from {}: {}",
+                    path.as_ref().display(), err);
+                Bindings::defaults()
+            },
+        }
+    }
+
+    /// The actions triggered by `key`.
+    pub fn actions(&self, key: Keycode) -> Vec<Action> {
+        Action::ALL.iter().cloned()
+            .filter(|action| self.keys[action].contains(&key))
+            .collect()
+    }
+
+    /// The actions triggered by the controller button `button`.
+    pub fn button_actions(&self, button: Button) -> Vec<Action> {
+        Action::ALL.iter().cloned()
+            .filter(|action| self.buttons[action].contains(&button))
+            .collect()
+    }
+
+    /// The keys which trigger `action`.
+    pub fn keys(&self, action: Action) -> &[Keycode] {
+        &self.keys[&action]
+    }
+
+    /// Replaces the keys which trigger `action`.
+    pub fn bind(&mut self, action: Action, keys: Vec<Keycode>) {
+        self.keys.insert(action, keys);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_overrides_listed_actions_only() {
+        let bindings = Bindings::parse("
+            # Comments and blank lines are skipped.
+
+            Fire = Return, Pad:b
+            MoveUp = W
+        ").unwrap();
+
+        assert_eq!(bindings.keys(Action::Fire), &[Keycode::Return]);
+        assert_eq!(bindings.button_actions(Button::B), vec![Action::Fire, Action::Weapon3]);
+        assert!(bindings.button_actions(Button::RightShoulder).is_empty());
+        assert_eq!(bindings.keys(Action::MoveUp), &[Keycode::W]);
+        assert_eq!(bindings.keys(Action::MoveDown), &Action::MoveDown.default_keys()[..]);
+    }
+
+    #[test]
+    fn parse_allows_unbinding() {
+        let bindings = Bindings::parse("Back =").unwrap();
+
+        assert!(bindings.keys(Action::Back).is_empty());
+        assert!(bindings.actions(Keycode::Escape).is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_malformed_lines() {
+        for source in &["Fire", "Jump = Space", "Fire = NotAKey", "Fire = Pad:nope"] {
+            let err = Bindings::parse(source).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().starts_with("line 1:"));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::phi::Phi;
+use crate::phi::data::Rectangle;
+use crate::phi::error::{PhiError, Result};
+use crate::phi::gfx::Sprite;
+use crate::phi::text::{wrap_lines, Align, TextBlock};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+
+struct Glyph<'r> {
+    sprite: Sprite<'r>,
+    /// How far to the right the next glyph is drawn.
+    advance: f64,
+}
+
+/// A font drawn from a spritesheet holding every glyph, rather than rendered
+/// by SDL_ttf. Laying text out only copies handles to the spritesheet, so that
+/// it can change on every frame at no cost.
+///
+/// The font is described by a metrics file:
+///
+///     # Comments start with a hash.
+///     image retro.png
+///     line_height 18
+///     # glyph <char> <x> <y> <w> <h> [advance]
+///     glyph A 0 0 8 16 9
+///     glyph space 0 0 0 16 6
+///
+/// The image is found relative to the metrics file, and the rectangles are
+/// given in pixels of that image. A glyph advances by its width unless told
+/// otherwise. Characters without a glyph are drawn as `?` if there is one,
+/// and skipped otherwise. See `assets/fonts/digits.fnt` for an example.
+pub struct BitmapFont<'r> {
+    glyphs: HashMap<char, Glyph<'r>>,
+    line_height: f64,
+}
+
+/// The content of a metrics file, before the image is loaded.
+#[derive(Debug, PartialEq)]
+struct Metrics {
+    image: String,
+    line_height: f64,
+    glyphs: Vec<(char, Rectangle, f64)>,
+}
+
+impl Metrics {
+    /// Reads the content of a metrics file. Errors tell on which line the
+    /// file is wrong.
+    fn parse(source: &str) -> std::result::Result<Metrics, String> {
+        let mut image = None;
+        let mut line_height = None;
+        let mut glyphs = vec![];
+
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |message: String| format!("line {}: {}", i + 1, message);
+            let words: Vec<_> = line.split_whitespace().collect();
+            let number = |word: &str| word.parse::<f64>()
+                .map_err(|_| invalid(format!("`{}` is not a number", word)));
+
+            match (words[0], words.len()) {
+                ("image", 2) =>
+                    image = Some(words[1].to_string()),
+
+                ("line_height", 2) =>
+                    line_height = Some(number(words[1])?),
+
+                ("glyph", 6) | ("glyph", 7) => {
+                    let c = match words[1] {
+                        "space" => ' ',
+                        word if word.chars().count() == 1 => word.chars().next().unwrap(),
+                        word => return Err(invalid(format!("`{}` is not a single character", word))),
+                    };
+
+                    let rect = Rectangle {
+                        x: number(words[2])?,
+                        y: number(words[3])?,
+                        w: number(words[4])?,
+                        h: number(words[5])?,
+                    };
+
+                    let advance = match words.get(6) {
+                        Some(word) => number(word)?,
+                        None => rect.w,
+                    };
+
+                    glyphs.push((c, rect, advance));
+                },
+
+                _ => return Err(invalid(format!("unexpected `{}`", line))),
+            }
+        }
+
+        Ok(Metrics {
+            image: image.ok_or("missing `image`")?,
+            line_height: line_height.ok_or("missing `line_height`")?,
+            glyphs: glyphs,
+        })
+    }
+}
+
+impl<'r> BitmapFont<'r> {
+    /// Reads the metrics file at `path`, then loads the image it refers to.
+    pub fn load(phi: &mut Phi<'_, 'r>, path: &str) -> Result<BitmapFont<'r>> {
+        let metrics = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|source| Metrics::parse(&source))
+            .map_err(|err| PhiError::asset(path, err))?;
+
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        let image_path = dir.join(&metrics.image).to_string_lossy().into_owned();
+        let sheet = phi.assets.texture(&phi.renderer, &image_path)?;
+
+        let mut glyphs = HashMap::new();
+
+        for (c, rect, advance) in metrics.glyphs {
+            glyphs.insert(c, Glyph { sprite: sheet.region(rect)?, advance: advance });
+        }
+
+        Ok(BitmapFont {
+            glyphs: glyphs,
+            line_height: metrics.line_height,
+        })
+    }
+
+    fn glyph(&self, c: char) -> Option<&Glyph<'r>> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// The width of a single line of text.
+    fn line_width(&self, line: &str) -> f64 {
+        line.chars()
+            .filter_map(|c| self.glyph(c))
+            .map(|glyph| glyph.advance)
+            .sum()
+    }
+
+    /// The size the given text has when laid out without a maximum width,
+    /// i.e. only broken at its newlines.
+    pub fn text_size(&self, text: &str) -> (f64, f64) {
+        self.layout_text(text, None, Align::Left).size()
+    }
+
+    /// Lays `text` out the same way as `Phi::layout_text`, breaking lines
+    /// so that none is wider than `max_width` if there is one.
+    pub fn layout_text(&self, text: &str, max_width: Option<f64>, align: Align) -> TextBlock<'r> {
+        // Measuring never fails, and neither does wrapping then.
+        let lines = wrap_lines(text, max_width, |line| Ok(self.line_width(line)))
+            .unwrap_or_default();
+
+        let block_w = lines.iter()
+            .map(|line| self.line_width(line))
+            .fold(0.0, f64::max);
+
+        let mut pieces = vec![];
+
+        for (i, line) in lines.iter().enumerate() {
+            let mut x = align.offset(block_w, self.line_width(line));
+            let y = self.line_height * i as f64;
+
+            for glyph in line.chars().filter_map(|c| self.glyph(c)) {
+                let (w, h) = glyph.sprite.size();
+
+                if w > 0.0 {
+                    pieces.push((glyph.sprite.clone(), Rectangle { x: x, y: y, w: w, h: h }));
+                }
+
+                x += glyph.advance;
+            }
+        }
+
+        TextBlock::new(pieces, (block_w, self.line_height * lines.len() as f64))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phi::headless::testing;
+
+    #[test]
+    fn parse_metrics() {
+        let metrics = Metrics::parse("
+            # A comment
+            image font.png
+            line_height 18
+            glyph A 0 0 8 16 9
+            glyph space 0 0 0 16
+        ").unwrap();
+
+        assert_eq!(metrics, Metrics {
+            image: "font.png".to_string(),
+            line_height: 18.0,
+            glyphs: vec![
+                ('A', Rectangle { x: 0.0, y: 0.0, w: 8.0, h: 16.0 }, 9.0),
+                (' ', Rectangle { x: 0.0, y: 0.0, w: 0.0, h: 16.0 }, 0.0),
+            ],
+        });
+    }
+
+    #[test]
+    fn parse_metrics_rejects_malformed_files() {
+        assert_eq!(Metrics::parse("line_height 18"), Err("missing `image`".to_string()));
+        assert_eq!(Metrics::parse("image a.png"), Err("missing `line_height`".to_string()));
+        assert_eq!(Metrics::parse("image a.png\nglyph AB 0 0 1 1"),
+                   Err("line 2: `AB` is not a single character".to_string()));
+        assert_eq!(Metrics::parse("image a.png\nglyph A 0 0 one 1"),
+                   Err("line 2: `one` is not a number".to_string()));
+        assert_eq!(Metrics::parse("image a.png b.png"),
+                   Err("line 1: unexpected `image a.png b.png`".to_string()));
+    }
+
+    #[test]
+    fn lays_out_the_sample_font() {
+        let mut headless = testing::start_blank();
+        let font = BitmapFont::load(headless.phi(), "assets/fonts/digits.fnt").unwrap();
+
+        // Digits advance by 6 pixels, spaces by 4, and unknown characters
+        // are drawn as `?`.
+        assert_eq!(font.text_size("12:30"), (30.0, 8.0));
+        assert_eq!(font.text_size("1 2"), (16.0, 8.0));
+        assert_eq!(font.text_size("x"), (6.0, 8.0));
+        assert_eq!(font.text_size("123\n4"), (18.0, 16.0));
+
+        assert_eq!(font.layout_text("12 34 56", Some(30.0), Align::Right).size(), (28.0, 16.0));
+    }
+}
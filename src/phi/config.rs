@@ -0,0 +1,159 @@
+use crate::phi::{Phi, View, LOGICAL_HEIGHT, LOGICAL_WIDTH, TICKS_PER_SECOND};
+use crate::phi::error::Result;
+use crate::phi::headless::Headless;
+use crate::phi::settings::Settings;
+use sdl2::mixer::{AudioFormat, AUDIO_S16LSB};
+
+
+/// How the audio device is opened.
+#[derive(Clone, Debug)]
+pub struct AudioConfig {
+    /// The sampling rate, in Hz.
+    pub frequency: i32,
+    /// How every sample is stored, e.g. `AUDIO_S16LSB` for 16-bit signed
+    /// integers.
+    pub format: AudioFormat,
+    /// 1 for mono, 2 for stereo.
+    pub channels: i32,
+    /// How many samples are mixed at once. Smaller chunks lower the latency
+    /// of the sounds, but may make them crackle on slow machines.
+    pub chunk_size: i32,
+    /// How many sound effects may play at once.
+    pub voices: usize,
+}
+
+impl Default for AudioConfig {
+    fn default() -> AudioConfig {
+        AudioConfig {
+            frequency: 44100,
+            format: AUDIO_S16LSB,
+            channels: 2,
+            chunk_size: 1024,
+            voices: 32,
+        }
+    }
+}
+
+/// Everything about the window and the game loop which a game may choose
+/// before starting. The views always draw at the logical resolution, which is
+/// scaled to fit the window whatever its size.
+#[derive(Clone, Debug)]
+pub struct PhiConfig {
+    pub title: String,
+    /// The size of the window when the game starts, in pixels.
+    pub window_size: (u32, u32),
+    pub resizable: bool,
+    pub opengl: bool,
+    /// Always wait for the screen to refresh before presenting a frame, even
+    /// if the player turned vsync off in the settings.
+    pub vsync: bool,
+    /// How many times per second the views are updated.
+    pub ticks_per_second: f64,
+    /// How many frames per second we draw at most, or 0 for no limit, until
+    /// the player picks another limit in the settings.
+    pub max_fps: u32,
+    pub audio: AudioConfig,
+}
+
+impl Default for PhiConfig {
+    fn default() -> PhiConfig {
+        PhiConfig {
+            title: String::new(),
+            window_size: (LOGICAL_WIDTH, LOGICAL_HEIGHT),
+            resizable: true,
+            opengl: true,
+            vsync: false,
+            ticks_per_second: TICKS_PER_SECOND,
+            max_fps: Settings::default().max_fps,
+            audio: AudioConfig::default(),
+        }
+    }
+}
+
+impl PhiConfig {
+    /// The settings of a player who never changed them.
+    pub(super) fn default_settings(&self) -> Settings {
+        Settings {
+            max_fps: self.max_fps,
+            ..Settings::default()
+        }
+    }
+}
+
+/// Sets up a game before running it, starting with the view built by `init`:
+///
+///     PhiBuilder::new("Asteroids", |phi| Ok(Box::new(MenuView::new(phi)?)))
+///         .window_size(1280, 720)
+///         .ticks_per_second(120.0)
+///         .run()
+///
+/// Whatever is not set keeps the value it has in `PhiConfig::default`.
+pub struct PhiBuilder<F> {
+    config: PhiConfig,
+    init: F,
+}
+
+impl<F> PhiBuilder<F>
+where F: Fn(&mut Phi) -> Result<Box<dyn View>> {
+    pub fn new(title: &str, init: F) -> PhiBuilder<F> {
+        PhiBuilder {
+            config: PhiConfig {
+                title: title.to_string(),
+                ..PhiConfig::default()
+            },
+            init: init,
+        }
+    }
+
+    /// Replaces the whole configuration at once, title included.
+    pub fn config(mut self, config: PhiConfig) -> PhiBuilder<F> {
+        self.config = config;
+        self
+    }
+
+    pub fn window_size(mut self, width: u32, height: u32) -> PhiBuilder<F> {
+        self.config.window_size = (width, height);
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> PhiBuilder<F> {
+        self.config.resizable = resizable;
+        self
+    }
+
+    pub fn opengl(mut self, opengl: bool) -> PhiBuilder<F> {
+        self.config.opengl = opengl;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> PhiBuilder<F> {
+        self.config.vsync = vsync;
+        self
+    }
+
+    pub fn ticks_per_second(mut self, ticks_per_second: f64) -> PhiBuilder<F> {
+        self.config.ticks_per_second = ticks_per_second;
+        self
+    }
+
+    pub fn max_fps(mut self, max_fps: u32) -> PhiBuilder<F> {
+        self.config.max_fps = max_fps;
+        self
+    }
+
+    pub fn audio(mut self, audio: AudioConfig) -> PhiBuilder<F> {
+        self.config.audio = audio;
+        self
+    }
+
+    /// Runs the game until a view asks to quit. See `phi::spawn`.
+    pub fn run(self) -> Result<()> {
+        super::run(&self.config, self.init)
+    }
+
+    /// Starts the game without showing it, e.g. to test it under the same
+    /// configuration as when it runs for real. See `Headless`.
+    pub fn headless<'ttf, 'r>(self, seed: u64) -> Result<Headless<'ttf, 'r>> {
+        Headless::new(&self.config, seed, self.init)
+    }
+}
@@ -0,0 +1,177 @@
+use crate::phi::{Action, Events};
+use sdl2::controller::{Axis, Button};
+
+
+/// Below this distance from its center, in [0, 1], the stick is considered
+/// to be at rest. Sticks rarely go back exactly to the center, so that the
+/// ship would otherwise drift.
+const DEADZONE: f64 = 0.25;
+
+/// Beyond this distance from the center on an axis, the stick also presses
+/// the matching movement action, e.g. to navigate menus.
+const DIGITAL_THRESHOLD: f64 = 0.5;
+
+impl Events {
+    /// Starts listening to the controller with the given joystick index.
+    pub(super) fn open_controller(&mut self, index: u32) {
+        let controller = match self.controllers {
+            Some(ref subsystem) => subsystem.open(index),
+            None => return,
+        };
+
+        match controller {
+            Ok(controller) => {
+                println!("Controller connected: {}", controller.name());
+                self.opened.push(controller);
+            },
+
+            Err(err) => println!("Could not open controller {}: {}", index, err),
+        }
+    }
+
+    /// Forgets the controller with the given instance id, which has been
+    /// unplugged. Lets go of its buttons, and of the stick if it was held on
+    /// that controller, so that the ship does not keep moving on its own.
+    pub(super) fn close_controller(&mut self, instance_id: u32) {
+        self.opened.retain(|controller| controller.instance_id() != instance_id);
+
+        let buttons: Vec<_> = self.held_buttons.iter()
+            .filter(|&&(id, _)| id == instance_id)
+            .map(|&(_, button)| button)
+            .collect();
+
+        for button in buttons {
+            self.release_button(instance_id, button);
+        }
+
+        if self.stick_owner == Some(instance_id) || self.opened.is_empty() {
+            self.stick_owner = None;
+            self.set_stick((0.0, 0.0));
+        }
+    }
+
+    /// Called whenever a button of the controller with the given instance id
+    /// is pressed.
+    pub(super) fn press_button(&mut self, instance_id: u32, button: Button) {
+        if self.held_buttons.contains(&(instance_id, button)) {
+            return;
+        }
+
+        self.held_buttons.push((instance_id, button));
+
+        for action in self.bindings.button_actions(button) {
+            self.press(action);
+        }
+    }
+
+    /// Called whenever a button of the controller with the given instance id
+    /// is released.
+    pub(super) fn release_button(&mut self, instance_id: u32, button: Button) {
+        let len = self.held_buttons.len();
+        self.held_buttons.retain(|&held| held != (instance_id, button));
+
+        // The button may have been pressed before the controller was opened.
+        if self.held_buttons.len() == len {
+            return;
+        }
+
+        for action in self.bindings.button_actions(button) {
+            self.release(action);
+        }
+    }
+
+    /// Called whenever an axis of the controller with the given instance id
+    /// moves.
+    pub(super) fn move_stick(&mut self, instance_id: u32, axis: Axis, value: i16) {
+        // `value` is in [-32768, 32767].
+        let value = (value as f64 / 32767.0).max(-1.0);
+
+        if let Axis::LeftX | Axis::LeftY = axis {
+            self.stick_owner = Some(instance_id);
+        }
+
+        match axis {
+            Axis::LeftX => self.set_stick((value, self.stick.1)),
+            Axis::LeftY => self.set_stick((self.stick.0, value)),
+            _ => {},
+        }
+    }
+
+    /// Moves the stick, pressing and releasing the movement actions whenever
+    /// it crosses `DIGITAL_THRESHOLD`.
+    fn set_stick(&mut self, stick: (f64, f64)) {
+        let before = stick_actions(self.stick);
+        let after = stick_actions(stick);
+        self.stick = stick;
+
+        for action in &before {
+            if !after.contains(action) {
+                self.release(*action);
+            }
+        }
+
+        for action in &after {
+            if !before.contains(action) {
+                self.press(*action);
+            }
+        }
+    }
+
+    /// The position of the stick once the deadzone is applied. Each axis is
+    /// in [-1, 1], and the distance to the center grows from 0 at the edge of
+    /// the deadzone to 1 when the stick is pushed all the way.
+    pub fn stick(&self) -> (f64, f64) {
+        let (x, y) = self.stick;
+        let distance = (x * x + y * y).sqrt();
+
+        if distance < DEADZONE {
+            return (0.0, 0.0);
+        }
+
+        let scale = ((distance - DEADZONE) / (1.0 - DEADZONE)).min(1.0) / distance;
+        (x * scale, y * scale)
+    }
+
+    /// The direction in which the player wants to move, with a length of at
+    /// most 1. The keyboard and d-pad take precedence over the stick, and
+    /// always move at full speed.
+    pub fn movement(&self) -> (f64, f64) {
+        let axis = |neg, pos| match (self.held[neg as usize] > 0, self.held[pos as usize] > 0) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+
+        // The stick itself holds the movement actions when pushed far enough,
+        // which we must not mistake for digital input.
+        let stick = stick_actions(self.stick);
+        let digital = [Action::MoveLeft, Action::MoveRight, Action::MoveUp, Action::MoveDown]
+            .iter()
+            .any(|&action| self.held[action as usize] > stick.contains(&action) as u8);
+
+        if !digital {
+            return self.stick();
+        }
+
+        let (dx, dy) = (axis(Action::MoveLeft, Action::MoveRight),
+                        axis(Action::MoveUp, Action::MoveDown));
+
+        if dx != 0.0 && dy != 0.0 {
+            (dx * 0.5f64.sqrt(), dy * 0.5f64.sqrt())
+        } else {
+            (dx, dy)
+        }
+    }
+}
+
+/// The movement actions held by a stick at the given position.
+fn stick_actions((x, y): (f64, f64)) -> Vec<Action> {
+    let mut actions = vec![];
+
+    if x <= -DIGITAL_THRESHOLD { actions.push(Action::MoveLeft); }
+    if x >= DIGITAL_THRESHOLD { actions.push(Action::MoveRight); }
+    if y <= -DIGITAL_THRESHOLD { actions.push(Action::MoveUp); }
+    if y >= DIGITAL_THRESHOLD { actions.push(Action::MoveDown); }
+
+    actions
+}
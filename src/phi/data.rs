@@ -36,11 +36,24 @@ impl Rectangle {
         (x, y)
     }
     
+    /// Returns the rectangle which is `alpha` of the way from `self` to `to`,
+    /// where `alpha` is usually in [0, 1].
+    pub fn lerp(self, to: Rectangle, alpha: f64) -> Rectangle {
+        Rectangle {
+            x: self.x + (to.x - self.x) * alpha,
+            y: self.y + (to.y - self.y) * alpha,
+            w: self.w + (to.w - self.w) * alpha,
+            h: self.h + (to.h - self.h) * alpha,
+        }
+    }
+    
     /// Generates an SDL-compatible Rect equivalent to `self`.
-    /// Panics if it could not be created, for example if a
-    /// coordinate of a corner overflows an `i32`.
+    /// Returns `None` if it could not be created, for example if the
+    /// rectangle has a negative size.
     pub fn to_sdl(self) -> Option<SdlRect> {
-        assert!(self.w >= 0.0 && self.h >= 0.0);
+        if !(self.w >= 0.0 && self.h >= 0.0) {
+            return None;
+        }
 
         Some(SdlRect::new(self.x as i32, self.y as i32, self.w as u32, self.h as u32))
     }
@@ -0,0 +1,132 @@
+use sdl2::video::{FullscreenType, Window};
+
+
+/// How the game is shown on the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// In a window which the player may move and resize.
+    Windowed,
+    /// In a window without borders which covers the whole screen, at the
+    /// resolution of the desktop. Switching to and from other programs is
+    /// instant.
+    Borderless,
+    /// The game takes over the screen, at the resolution of its choosing.
+    Exclusive,
+}
+
+impl DisplayMode {
+    /// Every mode, in the order in which the fullscreen hotkey goes through
+    /// them.
+    pub const ALL: &'static [DisplayMode] = &[
+        DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Exclusive,
+    ];
+
+    /// The name of the mode, as written in the settings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "windowed",
+            DisplayMode::Borderless => "borderless",
+            DisplayMode::Exclusive => "exclusive",
+        }
+    }
+
+    /// The mode with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<DisplayMode> {
+        DisplayMode::ALL.iter().cloned().find(|mode| mode.name() == name)
+    }
+
+    /// The mode following this one, wrapping around after the last.
+    pub fn next(self) -> DisplayMode {
+        let i = DisplayMode::ALL.iter().position(|&mode| mode == self).unwrap();
+        DisplayMode::ALL[(i + 1) % DisplayMode::ALL.len()]
+    }
+
+    fn fullscreen_type(self) -> FullscreenType {
+        match self {
+            DisplayMode::Windowed => FullscreenType::Off,
+            DisplayMode::Borderless => FullscreenType::Desktop,
+            DisplayMode::Exclusive => FullscreenType::True,
+        }
+    }
+}
+
+/// A resolution which the screen supports in exclusive fullscreen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Resolution {
+    pub w: i32,
+    pub h: i32,
+    /// In Hz, or 0 if unknown.
+    pub refresh_rate: i32,
+}
+
+impl Resolution {
+    /// Reads a resolution written as by `to_string`, e.g. `1920x1080@60`.
+    pub fn parse(source: &str) -> Option<Resolution> {
+        let mut sides = source.splitn(2, '@');
+        let mut size = sides.next()?.splitn(2, 'x');
+
+        Some(Resolution {
+            w: size.next()?.trim().parse().ok()?,
+            h: size.next()?.trim().parse().ok()?,
+            refresh_rate: sides.next().map_or(Some(0), |rate| rate.trim().parse().ok())?,
+        })
+    }
+
+    fn from_sdl(mode: sdl2::video::DisplayMode) -> Resolution {
+        Resolution { w: mode.w, h: mode.h, refresh_rate: mode.refresh_rate }
+    }
+}
+
+impl std::fmt::Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}x{}@{}", self.w, self.h, self.refresh_rate)
+    }
+}
+
+/// The resolutions supported by the screen on which the window is, from the
+/// largest to the smallest. Those which SDL cannot tell are left out.
+pub fn resolutions(window: &Window) -> Vec<Resolution> {
+    let video = window.subsystem();
+    let display = match window.display_index() {
+        Ok(display) => display,
+        Err(_) => return vec![],
+    };
+
+    let count = video.num_display_modes(display).unwrap_or(0);
+    let mut resolutions: Vec<_> = (0..count)
+        .filter_map(|i| video.display_mode(display, i).ok())
+        .map(Resolution::from_sdl)
+        .collect();
+
+    // The same resolution may be listed once per pixel format, which makes no
+    // difference to the player. SDL does not list those next to each other,
+    // so that we sort them first.
+    resolutions.sort_by(|a, b| (b.w, b.h, b.refresh_rate).cmp(&(a.w, a.h, a.refresh_rate)));
+    resolutions.dedup();
+    resolutions
+}
+
+/// Shows the `window` in the given `mode`. In exclusive fullscreen, the
+/// screen switches to `resolution`, or keeps that of the desktop if it is
+/// `None` or unsupported.
+pub fn apply(window: &mut Window, mode: DisplayMode, resolution: Option<Resolution>)
+-> Result<(), String> {
+    if mode == DisplayMode::Exclusive {
+        let desktop = window.display_index()
+            .and_then(|display| window.subsystem().desktop_display_mode(display))?;
+
+        let sdl_mode = resolution
+            .filter(|resolution| resolutions(window).contains(resolution))
+            .map(|resolution| sdl2::video::DisplayMode {
+                w: resolution.w,
+                h: resolution.h,
+                refresh_rate: resolution.refresh_rate,
+                ..desktop
+            })
+            .unwrap_or(desktop);
+
+        window.set_display_mode(Some(sdl_mode))?;
+    }
+
+    window.set_fullscreen(mode.fullscreen_type())
+}
@@ -0,0 +1,72 @@
+use crate::phi::data::Rectangle;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+
+/// Everything that can go wrong in Phi, described well enough to be shown to
+/// the player as is.
+#[derive(Clone, Debug)]
+pub enum PhiError {
+    /// Part of SDL, e.g. the video subsystem or the window, could not be
+    /// started.
+    Init { what: &'static str, reason: String },
+    /// A file the game needs, e.g. an image or a font, could not be loaded.
+    Asset { path: String, reason: String },
+    /// A sprite was asked for a region which it does not contain.
+    Region { rect: Rectangle },
+    /// Some text could not be turned into a sprite.
+    Text { text: String, reason: String },
+    /// SDL failed to do something once running, e.g. to read back the pixels
+    /// of a frame.
+    Sdl { what: &'static str, reason: String },
+}
+
+pub type Result<T> = ::std::result::Result<T, PhiError>;
+
+impl PhiError {
+    pub fn init<E: ToString>(what: &'static str, reason: E) -> PhiError {
+        PhiError::Init { what, reason: reason.to_string() }
+    }
+
+    pub fn asset<E: ToString>(path: &str, reason: E) -> PhiError {
+        PhiError::Asset { path: path.to_string(), reason: reason.to_string() }
+    }
+
+    pub fn sdl<E: ToString>(what: &'static str, reason: E) -> PhiError {
+        PhiError::Sdl { what, reason: reason.to_string() }
+    }
+}
+
+impl fmt::Display for PhiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PhiError::Init { what, ref reason } =>
+                write!(f, "could not start {}: {}", what, reason),
+
+            PhiError::Asset { ref path, ref reason } => {
+                write!(f, "could not load {}: {}", path, reason)?;
+
+                // The most likely cause by far is that the game was not started
+                // from the directory holding its assets.
+                match Path::new(path).parent() {
+                    Some(dir) if dir != Path::new("") && !dir.is_dir() =>
+                        write!(f, " (the directory `{}` does not exist, was the game \
+                                   started from the right place?)", dir.display()),
+                    _ => Ok(()),
+                }
+            },
+
+            PhiError::Region { rect } =>
+                write!(f, "the region {:?} is outside of the sprite", rect),
+
+            PhiError::Text { ref text, ref reason } =>
+                write!(f, "could not render `{}`: {}", text, reason),
+
+            PhiError::Sdl { what, ref reason } =>
+                write!(f, "could not {}: {}", what, reason),
+        }
+    }
+}
+
+impl Error for PhiError {}
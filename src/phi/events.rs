@@ -1,80 +1,374 @@
 macro_rules! struct_events {
     (
-        keyboard: { $( $k_alias:ident : $k_sdl:ident ),* },
+        actions: { $( $action:ident : keys [ $( $k_sdl:ident ),* ], pad [ $( $b_sdl:ident ),* ] ),* },
         else: { $( $e_alias:ident : $e_sdl:pat ),* }
     )
     => {
         use sdl2::EventPump;
+        use sdl2::GameControllerSubsystem;
+        use sdl2::controller::{Button, GameController};
+        use sdl2::keyboard::Keycode;
+
+
+        /// The logical inputs of the game. Views only ever look at those, and
+        /// never at the physical keys, which can be rebound at will.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Action {
+            $( $action ),*
+        }
+
+        impl Action {
+            /// Every action, in declaration order.
+            pub const ALL: &'static [Action] = &[ $( Action::$action ),* ];
+
+            /// The name of the action, as written in the bindings file.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $( Action::$action => stringify!($action) ),*
+                }
+            }
+
+            /// The action with the given name, if there is one.
+            pub fn from_name(name: &str) -> Option<Action> {
+                Action::ALL.iter().cloned().find(|action| action.name() == name)
+            }
+
+            /// The keys bound to the action when no bindings file overrides
+            /// them.
+            pub fn default_keys(self) -> Vec<Keycode> {
+                match self {
+                    $( Action::$action => vec![ $( Keycode::$k_sdl ),* ] ),*
+                }
+            }
+
+            /// The controller buttons bound to the action when no bindings
+            /// file overrides them.
+            pub fn default_buttons(self) -> Vec<Button> {
+                match self {
+                    $( Action::$action => vec![ $( Button::$b_sdl ),* ] ),*
+                }
+            }
+        }
 
 
         pub struct ImmediateEvents {
-            $( pub $k_alias : Option<bool> , )*
+            /// For every action, `Some(true)` if it was pressed since the last
+            /// frame, `Some(false)` if it was released, and `None` otherwise.
+            actions: Vec<Option<bool>>,
             $( pub $e_alias : bool , )*
-            resize: Option<(u32, u32)>
+            /// The new size of the drawable area, in physical pixels, if the
+            /// window was resized since the last frame. Views draw in logical
+            /// coordinates, which are not affected.
+            pub resize: Option<(u32, u32)>,
+
+            /// Same as `actions`, for the left, middle and right buttons of
+            /// the mouse.
+            mouse_buttons: [Option<bool>; 3],
+            /// Whether the mouse moved since the last frame.
+            pub mouse_moved: bool,
+            /// How far the wheel was scrolled since the last frame,
+            /// horizontally and vertically. Scrolling up gives a positive `y`.
+            pub wheel: (i32, i32),
         }
 
         impl ImmediateEvents {
             pub fn new() -> ImmediateEvents {
                 ImmediateEvents {
-                    $( $k_alias: None , )*
+                    actions: vec![None; Action::ALL.len()],
                     $( $e_alias: false , )*
-                    resize: None
+                    resize: None,
+
+                    mouse_buttons: [None; 3],
+                    mouse_moved: false,
+                    wheel: (0, 0),
                 }
             }
+
+            /// Whether `action` started being held since the last frame.
+            pub fn pressed(&self, action: Action) -> bool {
+                self.actions[action as usize] == Some(true)
+            }
+
+            /// Whether `action` stopped being held since the last frame.
+            pub fn released(&self, action: Action) -> bool {
+                self.actions[action as usize] == Some(false)
+            }
         }
 
 
         pub struct Events {
             pump: EventPump,
             pub now: ImmediateEvents,
+            pub bindings: Bindings,
+
+            /// For every action, the number of keys, buttons and stick
+            /// directions bound to it which are currently held down.
+            held: Vec<u8>,
 
-            $( pub $k_alias: bool ),*
+            /// `None` if the game controller subsystem could not be started.
+            controllers: Option<GameControllerSubsystem>,
+            /// The controllers which are plugged in. They are closed once
+            /// dropped, so we must keep them around.
+            opened: Vec<GameController>,
+            /// The position of the left stick, each axis in [-1, 1], before
+            /// the deadzone is applied.
+            stick: (f64, f64),
+            /// The instance id of the controller whose stick moved last,
+            /// which is the one `stick` follows.
+            stick_owner: Option<u32>,
+            /// The buttons held down, along with the instance id of their
+            /// controller, so that they can be let go of if it is unplugged.
+            held_buttons: Vec<(u32, Button)>,
+
+            /// The position of the pointer, in logical coordinates.
+            mouse: (i32, i32),
+            /// Whether the left, middle and right buttons are held down.
+            mouse_held: [bool; 3],
+
+            /// The size to report as `now.resize` on the next frame, if the
+            /// game itself changed it.
+            pending_resize: Option<(u32, u32)>,
         }
 
         impl Events {
-            pub fn new(pump: EventPump) -> Events {
+            pub fn new(pump: EventPump, bindings: Bindings,
+                       controllers: Option<GameControllerSubsystem>) -> Events {
                 Events {
                     pump: pump,
                     now: ImmediateEvents::new(),
+                    bindings: bindings,
+
+                    held: vec![0; Action::ALL.len()],
+
+                    controllers: controllers,
+                    opened: vec![],
+                    stick: (0.0, 0.0),
+                    stick_owner: None,
+                    held_buttons: vec![],
+
+                    mouse: (0, 0),
+                    mouse_held: [false; 3],
+
+                    pending_resize: None,
+                }
+            }
+
+            /// Reports the drawable area as resized to `size`, in physical
+            /// pixels, on the next frame. This is for changes which SDL may
+            /// not send an event for, e.g. switching display modes.
+            pub fn notify_resize(&mut self, size: (u32, u32)) {
+                self.pending_resize = Some(size);
+            }
+
+            /// Whether `action` is currently held down.
+            pub fn is_down(&self, action: Action) -> bool {
+                self.held[action as usize] > 0
+            }
+
+            /// The names of the inputs, in the order in which `encode` writes
+            /// them.
+            pub fn input_names() -> Vec<&'static str> {
+                let mut names: Vec<_> = Action::ALL.iter().map(|action| action.name()).collect();
+                $( names.push(stringify!($e_alias)); )*
+                names.push("stick_x");
+                names.push("stick_y");
+                names.push("mouse_x");
+                names.push("mouse_y");
+                names.push("mouse_buttons");
+                names.push("wheel_x");
+                names.push("wheel_y");
+                names
+            }
 
-                    $( $k_alias: false ),*
+            /// Encodes the state of every input as a single line, so that it
+            /// can be stored in a replay. The buttons are written as a word
+            /// with one character per input, followed by the stick position,
+            /// then by the state of the mouse.
+            pub fn encode(&self) -> String {
+                let mut word = String::new();
+
+                for &action in Action::ALL {
+                    word.push(match (self.is_down(action), self.now.actions[action as usize]) {
+                        (_, Some(true)) => 'P',
+                        (_, Some(false)) => 'R',
+                        (true, None) => '1',
+                        (false, None) => '0',
+                    });
+                }
+
+                $(
+                    word.push(if self.now.$e_alias { '1' } else { '0' });
+                )*
+
+                let mut mouse_word = String::new();
+
+                for i in 0..3 {
+                    mouse_word.push(match (self.mouse_held[i], self.now.mouse_buttons[i]) {
+                        (_, Some(true)) => 'P',
+                        (_, Some(false)) => 'R',
+                        (true, None) => '1',
+                        (false, None) => '0',
+                    });
+                }
+
+                // Whether the mouse moved is not stored, as it can be told
+                // from the position.
+                format!("{} {} {} {} {} {} {} {}", word, self.stick.0, self.stick.1,
+                    self.mouse.0, self.mouse.1, mouse_word, self.now.wheel.0, self.now.wheel.1)
+            }
+
+            /// Restores the state of every input from a line produced by
+            /// `encode`. The events which have just been pumped, e.g. closing
+            /// the window, are kept. Returns `None` if the line is malformed.
+            pub fn decode(&mut self, line: &str) -> Option<()> {
+                let mut words = line.split(' ');
+                let mut chars = words.next()?.chars();
+
+                for &action in Action::ALL {
+                    let (held, now) = match chars.next()? {
+                        'P' => (1, Some(true)),
+                        'R' => (0, Some(false)),
+                        '1' => (1, None),
+                        '0' => (0, None),
+                        _ => return None,
+                    };
+
+                    self.held[action as usize] = held;
+                    self.now.actions[action as usize] = now;
+                }
+
+                $(
+                    self.now.$e_alias |= match chars.next()? {
+                        '1' => true,
+                        '0' => false,
+                        _ => return None,
+                    };
+                )*
+
+                if chars.next().is_some() {
+                    return None;
+                }
+
+                self.stick = (words.next()?.parse().ok()?, words.next()?.parse().ok()?);
+
+                let mouse = (words.next()?.parse().ok()?, words.next()?.parse().ok()?);
+                self.now.mouse_moved = mouse != self.mouse;
+                self.mouse = mouse;
+
+                let mut mouse_chars = words.next()?.chars();
+
+                for i in 0..3 {
+                    let (held, now) = match mouse_chars.next()? {
+                        'P' => (true, Some(true)),
+                        'R' => (false, Some(false)),
+                        '1' => (true, None),
+                        '0' => (false, None),
+                        _ => return None,
+                    };
+
+                    self.mouse_held[i] = held;
+                    self.now.mouse_buttons[i] = now;
+                }
+
+                self.now.wheel = (words.next()?.parse().ok()?, words.next()?.parse().ok()?);
+
+                if words.next().is_some() {
+                    return None;
+                }
+
+                Some(())
+            }
+
+            /// Marks `action` as held by one more key, and reports it as
+            /// pressed if it was not held before.
+            fn press(&mut self, action: Action) {
+                if self.held[action as usize] == 0 {
+                    self.now.actions[action as usize] = Some(true);
+                }
+
+                self.held[action as usize] += 1;
+            }
+
+            /// Marks `action` as held by one less key, and reports it as
+            /// released once no key holds it anymore.
+            fn release(&mut self, action: Action) {
+                // The key may have been pressed before we got the focus, in
+                // which case we never saw it go down.
+                if self.held[action as usize] == 0 {
+                    return;
+                }
+
+                self.held[action as usize] -= 1;
+
+                if self.held[action as usize] == 0 {
+                    self.now.actions[action as usize] = Some(false);
                 }
             }
 
             pub fn pump(&mut self, renderer: &mut sdl2::render::WindowCanvas) {
                 self.now = ImmediateEvents::new();
+                self.now.resize = self.pending_resize.take();
+
+                // Collect the events first, as handling them requires
+                // borrowing `self` mutably.
+                let events: Vec<_> = self.pump.poll_iter().collect();
 
-                for event in self.pump.poll_iter() {
+                for event in events {
                     use sdl2::event::Event::*;
-                    use sdl2::event::WindowEvent::Resized;
-                    use sdl2::keyboard::Keycode::*;
+                    use sdl2::event::WindowEvent::{Resized, SizeChanged};
 
                     match event {
-                        KeyDown { keycode, .. } => match keycode {
-                            $(
-                                Some($k_sdl) => {
-                                    // Prevent multiple presses when keeping a key down
-                                    // Was previously not pressed?
-                                    if !self.$k_alias {
-                                        // Key pressed
-                                        self.now.$k_alias = Some(true);
-                                    }
-
-                                    self.$k_alias = true;
-                                }
-                            ),*
-                            _ => {}
+                        // Prevent multiple presses when keeping a key down
+                        KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+                            for action in self.bindings.actions(keycode) {
+                                self.press(action);
+                            }
+                        },
+
+                        KeyUp { keycode: Some(keycode), .. } => {
+                            for action in self.bindings.actions(keycode) {
+                                self.release(action);
+                            }
+                        },
+
+                        ControllerButtonDown { which, button, .. } =>
+                            self.press_button(which, button),
+
+                        ControllerButtonUp { which, button, .. } =>
+                            self.release_button(which, button),
+
+                        ControllerAxisMotion { which, axis, value, .. } =>
+                            self.move_stick(which, axis, value),
+
+                        ControllerDeviceAdded { which, .. } =>
+                            self.open_controller(which),
+
+                        ControllerDeviceRemoved { which, .. } =>
+                            self.close_controller(which),
+
+                        MouseMotion { x, y, .. } => {
+                            self.mouse = (x, y);
+                            self.now.mouse_moved = true;
                         },
 
-                        KeyUp { keycode, .. } => match keycode {
-                            $(
-                                Some($k_sdl) => {
-                                    // Key released
-                                    self.now.$k_alias = Some(false);
-                                    self.$k_alias = false;
-                                }
-                            ),*
-                            _ => {}
+                        MouseButtonDown { mouse_btn, x, y, .. } => {
+                            self.mouse = (x, y);
+                            self.mouse_button(mouse_btn, true);
+                        },
+
+                        MouseButtonUp { mouse_btn, x, y, .. } => {
+                            self.mouse = (x, y);
+                            self.mouse_button(mouse_btn, false);
+                        },
+
+                        MouseWheel { x, y, direction, .. } => {
+                            let sign = match direction {
+                                sdl2::mouse::MouseWheelDirection::Flipped => -1,
+                                _ => 1,
+                            };
+
+                            self.now.wheel.0 += sign * x;
+                            self.now.wheel.1 += sign * y;
                         },
 
                         $(
@@ -83,10 +377,14 @@ macro_rules! struct_events {
                             }
                         ),*
                         
-                        Window { win_event: Resized(w, h), .. } => {
-                            panic!("Decide which of these two lines to use!");
-                            //self.now.resize = Some((w, h));
-                            self.now.resize = Some(renderer.output_size().unwrap());
+                        // The size given by the event is that of the window, which
+                        // differs from the one of the drawable area on high-DPI
+                        // screens. We want the latter, as this is where we draw.
+                        Window { win_event: Resized(..), .. } |
+                        Window { win_event: SizeChanged(..), .. } => {
+                            if let Ok(size) = renderer.output_size() {
+                                self.now.resize = Some(size);
+                            }
                         },
 
                         _ => {}
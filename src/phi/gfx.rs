@@ -1,5 +1,6 @@
 use crate::phi::Phi;
 use crate::phi::data::Rectangle;
+use crate::phi::error::{PhiError, Result};
 use std::cell::RefCell;
 use std::path::Path;
 use std::rc::Rc;
@@ -46,16 +47,17 @@ impl<'r> Sprite<'r> {
     }
 
     /// Creates a new sprite from an image file located at the given path.
-    /// Returns `Some` if the file could be read, and `None` otherwise.
-    pub fn load(renderer: &WindowCanvas, path: &str) -> Option<Sprite<'r>> {
-        renderer.texture_creator().load_texture(Path::new(path)).ok().map(Sprite::new)
+    /// Prefer `Assets::texture`, which only reads every file once.
+    pub fn load(renderer: &WindowCanvas, path: &str) -> Result<Sprite<'r>> {
+        renderer.texture_creator().load_texture(Path::new(path))
+            .map(Sprite::new)
+            .map_err(|reason| PhiError::asset(path, reason))
     }
     
     /// Returns a new `Sprite` representing a sub-region of the current one.
     /// The provided `rect` is relative to the currently held region.
-    /// Returns `Some` if the `rect` is valid, i.e. included in the current
-    /// region, and `None` otherwise.
-    pub fn region(&self, rect: Rectangle) -> Option<Sprite> {
+    /// Fails unless the `rect` is valid, i.e. included in the current region.
+    pub fn region(&self, rect: Rectangle) -> Result<Sprite<'r>> {
         let new_src = Rectangle {
             x: rect.x + self.src.x,
             y: rect.y + self.src.y,
@@ -64,12 +66,12 @@ impl<'r> Sprite<'r> {
 
         // Verify that the requested region is inside of the current one
         if self.src.contains(new_src) {
-            Some(Sprite {
+            Ok(Sprite {
                 tex: self.tex.clone(),
                 src: new_src,
             })
         } else {
-            None
+            Err(PhiError::Region { rect: rect })
         }
     }
     
@@ -81,7 +83,13 @@ impl<'r> Sprite<'r> {
 
 impl<'r> Renderable for Sprite<'r> {
     fn render(&self, renderer: &mut WindowCanvas, dest: Rectangle) {
-        renderer.copy(&mut self.tex.borrow_mut(), self.src.to_sdl(), dest.to_sdl()).unwrap();
+        // SDL would stretch the sprite over the whole screen if we did not
+        // give it a destination, so we skip those which cannot be drawn.
+        // Failing to draw a single sprite is not worth ending the game for,
+        // so that errors are ignored, as they are for every shape we draw.
+        if let (Some(src), Some(dest)) = (self.src.to_sdl(), dest.to_sdl()) {
+            let _ = renderer.copy(&mut self.tex.borrow_mut(), src, dest);
+        }
     }
 }
 
@@ -129,11 +137,11 @@ impl<'r> AnimatedSprite<'r> {
         AnimatedSprite::new(sprites, 1.0 / fps)
     }
     
-    pub fn load_frames(phi: &mut Phi, descr: AnimatedSpriteDescr) -> Vec<Sprite<'r>> {
+    pub fn load_frames(phi: &mut Phi, descr: AnimatedSpriteDescr) -> Result<Vec<Sprite<'r>>> {
         // Read the asteroid's image from the filesystem and construct an
         // animated sprite out of it.
 
-        let spritesheet = Sprite::load(&mut phi.renderer, descr.image_path).unwrap();
+        let spritesheet = phi.assets.texture(&phi.renderer, descr.image_path)?;
         let mut frames = Vec::with_capacity(descr.total_frames);
 
         for yth in 0..descr.frames_high {
@@ -148,11 +156,11 @@ impl<'r> AnimatedSprite<'r> {
                         h: descr.frame_h,
                         x: descr.frame_w * xth as f64,
                         y: descr.frame_h * yth as f64,
-                    }).unwrap());
+                    })?);
             }
         }
 
-        frames
+        Ok(frames)
     }
 
     // The number of frames composing the animation.
@@ -0,0 +1,335 @@
+use crate::phi::{Events, FixedStep, Phi, View};
+use crate::phi::audio::Audio;
+use crate::phi::bindings::Bindings;
+use crate::phi::config::PhiConfig;
+use crate::phi::error::{PhiError, Result};
+use sdl2::event::Event;
+use sdl2::pixels::{Color, PixelFormatEnum};
+
+
+/// Runs views without a display server, e.g. in CI.
+///
+/// SDL is started with its dummy video and audio drivers, so that the window
+/// is never shown and its framebuffer is nothing more than an in-memory
+/// surface. We draw to it through a software canvas, which means that the
+/// pixels can be read back after every frame.
+///
+/// Frames are only run when `step` is called, which lets tests advance the
+/// game at their own pace.
+pub struct Headless<'ttf, 'r> {
+    sdl_context: sdl2::Sdl,
+    _video: sdl2::VideoSubsystem,
+    _image_context: sdl2::image::Sdl2ImageContext,
+    _mixer_context: Option<sdl2::mixer::Sdl2MixerContext>,
+
+    context: Phi<'ttf, 'r>,
+    stack: Vec<Box<dyn View>>,
+    running: bool,
+    clock: FixedStep,
+}
+
+impl<'ttf, 'r> Headless<'ttf, 'r> {
+    /// Creates an offscreen canvas the size of the window described by
+    /// `config`, then builds the first view with `init`. As with a window,
+    /// the views draw to the logical resolution, which is scaled to fit the
+    /// canvas. Runs started with the same `seed` and fed the same events are
+    /// identical.
+    pub fn new<F>(config: &PhiConfig, seed: u64, init: F) -> Result<Headless<'ttf, 'r>>
+    where F: Fn(&mut Phi) -> Result<Box<dyn View>> {
+        // Those must be set before SDL is initialized to have any effect.
+        std::env::set_var("SDL_VIDEODRIVER", "dummy");
+        std::env::set_var("SDL_AUDIODRIVER", "dummy");
+
+        let sdl_context = sdl2::init().map_err(|err| PhiError::init("SDL", err))?;
+        let video = sdl_context.video().map_err(|err| PhiError::init("the video subsystem", err))?;
+        let image_context = sdl2::image::init(sdl2::image::InitFlag::PNG)
+            .map_err(|err| PhiError::init("SDL_image", err))?;
+        let ttf_context = sdl2::ttf::init().map_err(|err| PhiError::init("SDL_ttf", err))?;
+
+        let mixer_context = super::audio::open(&config.audio);
+
+        let (width, height) = config.window_size;
+        let window = video.window(&config.title, width, height)
+            .hidden()
+            .build()
+            .map_err(|err| PhiError::init("the window", err))?;
+
+        let mut context = Phi::new(
+            Events::new(
+                sdl_context.event_pump().map_err(|err| PhiError::init("the event pump", err))?,
+                Bindings::defaults(),
+                None),
+            window.into_canvas().software().build()
+                .map_err(|err| PhiError::init("the renderer", err))?,
+            ttf_context,
+            Audio::new(mixer_context.is_some(), config.audio.voices),
+            config.default_settings(),
+            seed)?;
+
+        let stack = vec![init(&mut context)?];
+
+        Ok(Headless {
+            sdl_context,
+            _video: video,
+            _image_context: image_context,
+            _mixer_context: mixer_context,
+
+            context,
+            stack,
+            running: true,
+            clock: FixedStep::new(config.ticks_per_second),
+        })
+    }
+
+    /// Runs a single frame in which `elapsed` seconds have passed, which may
+    /// update the view any number of times depending on the tick rate.
+    /// Returns `false` if the view asked to quit, in which case any further
+    /// call does nothing.
+    pub fn step(&mut self, elapsed: f64) -> Result<bool> {
+        if self.running {
+            self.running = super::run_frame(&mut self.context, &mut self.stack, &mut self.clock, elapsed)?;
+        }
+
+        Ok(self.running)
+    }
+
+    /// Runs `frames` frames of `elapsed` seconds each, stopping early if the
+    /// view asked to quit. Returns whether the game is still running.
+    pub fn step_frames(&mut self, frames: usize, elapsed: f64) -> Result<bool> {
+        for _ in 0..frames {
+            if !self.step(elapsed)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Runs exactly one tick, then renders the frame.
+    pub fn step_tick(&mut self) -> Result<bool> {
+        let tick = self.clock.tick();
+        self.step(tick)
+    }
+
+    /// Queues an event, e.g. a key press, to be handled on the next frame.
+    pub fn push_event(&self, event: Event) -> Result<()> {
+        self.sdl_context.event()
+            .and_then(|events| events.push_event(event))
+            .map_err(|err| PhiError::sdl("queue an event", err))
+    }
+
+    /// Returns the content of the canvas after the last frame, as RGBA bytes
+    /// ordered row by row, starting from the top-left corner. This has the
+    /// size of the canvas, not that of the logical resolution.
+    pub fn pixels(&self) -> Result<Vec<u8>> {
+        self.context.renderer.read_pixels(None, PixelFormatEnum::RGBA32)
+            .map_err(|err| PhiError::sdl("read the pixels", err))
+    }
+
+    /// Returns the color of a single pixel of the canvas after the last frame.
+    /// Fails if the pixel lies outside of the canvas.
+    pub fn pixel(&self, x: u32, y: u32) -> Result<Color> {
+        let (w, h) = self.context.renderer.output_size()
+            .map_err(|err| PhiError::sdl("read the size of the canvas", err))?;
+
+        if x >= w || y >= h {
+            return Err(PhiError::sdl("read a pixel",
+                format!("({}, {}) is outside of the {}x{} canvas", x, y, w, h)));
+        }
+
+        let i = 4 * (y * w + x) as usize;
+        let pixels = self.pixels()?;
+
+        Ok(Color::RGBA(pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]))
+    }
+
+    /// Gives access to the context, e.g. to inspect the output size.
+    pub fn phi(&mut self) -> &mut Phi<'ttf, 'r> {
+        &mut self.context
+    }
+}
+
+
+/// Helpers for the tests which run views.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use crate::phi::ViewAction;
+    use std::ops::{Deref, DerefMut};
+    use std::sync::{Mutex, MutexGuard};
+
+    /// SDL may only be started once at a time, so that the tests which run it
+    /// must hold this lock while they do.
+    static SDL_LOCK: Mutex<()> = Mutex::new(());
+
+    /// A `Headless` which holds `SDL_LOCK` until it is dropped.
+    pub struct Locked<'ttf, 'r> {
+        // Fields are dropped in order, so that SDL quits before we unlock.
+        headless: Headless<'ttf, 'r>,
+        _sdl: MutexGuard<'static, ()>,
+    }
+
+    impl<'ttf, 'r> Deref for Locked<'ttf, 'r> {
+        type Target = Headless<'ttf, 'r>;
+
+        fn deref(&self) -> &Headless<'ttf, 'r> {
+            &self.headless
+        }
+    }
+
+    impl<'ttf, 'r> DerefMut for Locked<'ttf, 'r> {
+        fn deref_mut(&mut self) -> &mut Headless<'ttf, 'r> {
+            &mut self.headless
+        }
+    }
+
+    /// Runs the view built by `init` on an 80x60 canvas, once no other test
+    /// runs SDL.
+    pub fn start<'ttf, 'r, F>(init: F) -> Locked<'ttf, 'r>
+    where F: Fn(&mut Phi) -> Result<Box<dyn View>> {
+        // A test which failed while holding the lock did not leave SDL in a
+        // bad state, as it quit while unwinding.
+        let sdl = SDL_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let config = PhiConfig { window_size: (80, 60), ..PhiConfig::default() };
+
+        Locked {
+            headless: Headless::new(&config, 0, init).unwrap(),
+            _sdl: sdl,
+        }
+    }
+
+    /// Does nothing, for the tests which only need a running `Phi`.
+    pub struct Blank;
+
+    impl View for Blank {
+        fn update(&mut self, _: &mut Phi, _: f64) -> Result<ViewAction> {
+            Ok(ViewAction::None)
+        }
+
+        fn render(&self, _: &mut Phi, _: f64) {}
+    }
+
+    /// Runs `Blank`, see `start`.
+    pub fn start_blank<'ttf, 'r>() -> Locked<'ttf, 'r> {
+        start(|_| Ok(Box::new(Blank)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phi::ViewAction;
+
+    /// Fills the screen with a color which changes on every tick.
+    struct Fill {
+        ticks: u8,
+    }
+
+    impl View for Fill {
+        fn update(&mut self, _: &mut Phi, _: f64) -> Result<ViewAction> {
+            self.ticks += 1;
+            Ok(ViewAction::None)
+        }
+
+        fn render(&self, phi: &mut Phi, _: f64) {
+            phi.renderer.set_draw_color(Color::RGB(self.ticks, 0, 255));
+            phi.renderer.clear();
+        }
+    }
+
+    #[test]
+    fn steps_frames_and_reads_pixels() {
+        let mut headless = testing::start(|_| Ok(Box::new(Fill { ticks: 0 })));
+
+        let tick = headless.clock.tick();
+        assert!(headless.step_frames(3, tick).unwrap());
+        assert_eq!(headless.pixel(0, 0).unwrap(), Color::RGBA(3, 0, 255, 255));
+        assert_eq!(headless.pixel(79, 59).unwrap(), Color::RGBA(3, 0, 255, 255));
+        assert_eq!(headless.pixels().unwrap().len(), 80 * 60 * 4);
+
+        // A frame shorter than a tick renders without updating the view.
+        assert!(headless.step(0.0).unwrap());
+        assert_eq!(headless.pixel(40, 30).unwrap(), Color::RGBA(3, 0, 255, 255));
+
+        assert!(headless.pixel(80, 0).is_err());
+        assert!(headless.pixel(0, 60).is_err());
+
+        assert!(headless.step_tick().unwrap());
+        assert_eq!(headless.pixel(40, 30).unwrap(), Color::RGBA(4, 0, 255, 255));
+    }
+
+    /// Like `Fill`, but opens a `Dialog` on its second tick.
+    struct Opener {
+        ticks: u8,
+    }
+
+    impl View for Opener {
+        fn update(&mut self, _: &mut Phi, _: f64) -> Result<ViewAction> {
+            self.ticks += 1;
+
+            if self.ticks == 2 {
+                Ok(ViewAction::Push(Box::new(Dialog { ticks: 0 })))
+            } else {
+                Ok(ViewAction::None)
+            }
+        }
+
+        fn render(&self, phi: &mut Phi, _: f64) {
+            phi.renderer.set_draw_color(Color::RGB(self.ticks, 0, 255));
+            phi.renderer.clear();
+        }
+    }
+
+    /// Covers the left half of the screen, then closes on its third tick.
+    struct Dialog {
+        ticks: u8,
+    }
+
+    impl View for Dialog {
+        fn update(&mut self, _: &mut Phi, _: f64) -> Result<ViewAction> {
+            self.ticks += 1;
+
+            if self.ticks == 3 {
+                Ok(ViewAction::Pop)
+            } else {
+                Ok(ViewAction::None)
+            }
+        }
+
+        fn render(&self, phi: &mut Phi, _: f64) {
+            let (w, h) = phi.renderer.logical_size();
+            phi.renderer.set_draw_color(Color::RGB(0, 255, 0));
+            let _ = phi.renderer.fill_rect(sdl2::rect::Rect::new(0, 0, w / 2, h));
+        }
+
+        fn is_overlay(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn overlays_freeze_the_view_below() {
+        let mut headless = testing::start(|_| Ok(Box::new(Opener { ticks: 0 })));
+        let (dialog, below) = (Color::RGBA(0, 255, 0, 255), Color::RGBA(2, 0, 255, 255));
+
+        assert!(headless.step_tick().unwrap());
+        assert_eq!(headless.pixel(60, 30).unwrap(), Color::RGBA(1, 0, 255, 255));
+
+        // The dialog is drawn over the view which opened it, which is no longer
+        // updated.
+        for _ in 0..3 {
+            assert!(headless.step_tick().unwrap());
+            assert_eq!(headless.pixel(20, 30).unwrap(), dialog);
+            assert_eq!(headless.pixel(60, 30).unwrap(), below);
+        }
+
+        // Once the dialog is closed, the view below is drawn as it was left...
+        assert!(headless.step_tick().unwrap());
+        assert_eq!(headless.pixel(20, 30).unwrap(), below);
+        assert_eq!(headless.pixel(60, 30).unwrap(), below);
+
+        // ...then runs again.
+        assert!(headless.step_tick().unwrap());
+        assert_eq!(headless.pixel(20, 30).unwrap(), Color::RGBA(3, 0, 255, 255));
+    }
+}
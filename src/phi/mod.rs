@@ -1,199 +1,751 @@
 #[macro_use]
 mod events;
+pub mod assets;
+pub mod audio;
+pub mod bindings;
+pub mod bitmap_font;
+pub mod config;
+mod controller;
 pub mod data;
+pub mod display;
+pub mod error;
 pub mod gfx;
+pub mod headless;
+mod mouse;
+pub mod replay;
+pub mod settings;
+pub mod text;
 
-use self::gfx::Sprite;
+use self::assets::Assets;
+use self::audio::Audio;
+use self::bindings::Bindings;
+use self::config::{PhiBuilder, PhiConfig};
+use self::display::Resolution;
+use self::error::{PhiError, Result};
+use self::gfx::{CopySprite, Sprite};
+use self::replay::{Playback, Recorder, Replay};
+use self::settings::Settings;
+use self::text::{TextCache, TextKey};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use sdl2::render::WindowCanvas;
 use sdl2::pixels::Color;
 use sdl2::ttf::Sdl2TtfContext;
 use std::collections::HashMap;
+use std::io;
 use std::path::Path;
 
 
+/// Where the players may rebind the keys to their liking.
+const BINDINGS_PATH: &'static str = "bindings.cfg";
+
+/// Where the options changed at runtime are kept from one game to the next.
+const SETTINGS_PATH: &'static str = "settings.cfg";
+
+/// The font in which the frame rate and the seed are drawn, if the player
+/// asked for it.
+const DEBUG_FONT: &'static str = "assets/belligerent.ttf";
+
 struct_events! {
-    keyboard: {
-        key_escape: Escape,
-        key_up: Up,
-        key_down: Down,
-        key_left: Left,
-        key_right: Right,
-        key_space: Space,
-        key_return: Return,
+    actions: {
+        Back: keys [Escape], pad [Start],
+        MoveUp: keys [Up, W], pad [DPadUp],
+        MoveDown: keys [Down, S], pad [DPadDown],
+        MoveLeft: keys [Left, A], pad [DPadLeft],
+        MoveRight: keys [Right, D], pad [DPadRight],
+        Fire: keys [Space], pad [A, RightShoulder],
+        Confirm: keys [Return, Space], pad [A],
         
-        key_1: Num1,
-        key_2: Num2,
-        key_3: Num3
+        Weapon1: keys [Num1], pad [X],
+        Weapon2: keys [Num2], pad [Y],
+        Weapon3: keys [Num3], pad [B],
+
+        ToggleFullscreen: keys [F11], pad []
     },
     else: {
         quit: Quit { .. }
     }
 }
 
+/// The size of the screen as seen by the views, whatever the actual size of
+/// the window. The picture is scaled to fit the window, and black bars fill
+/// the remaining space if their aspect ratios differ.
+pub const LOGICAL_WIDTH: u32 = 800;
+pub const LOGICAL_HEIGHT: u32 = 600;
+
 /// Bundles the Phi abstractions in a single structure which
 /// can be passed easily between functions.
 pub struct Phi<'ttf, 'r> {
     pub events: Events,
     pub renderer: WindowCanvas,
+    pub assets: Assets<'r>,
+    pub audio: Audio,
+
+    /// The only source of randomness that views should use, so that a run
+    /// can be reproduced by starting it with the same seed.
+    pub rng: StdRng,
+    seed: u64,
+    /// Whether a run was already started from the startup seed.
+    startup_seed_used: bool,
+    replay: Replay,
+    settings: Settings,
+    /// How many frames were drawn during the last second, once known.
+    fps: Option<u16>,
     
     ttf_context: Sdl2TtfContext,
-    allocated_channels: i32,
     cached_fonts: HashMap<(&'static str, u16), sdl2::ttf::Font<'ttf, 'r>>,
+    cached_text: TextCache<'r>,
 }
 
 impl<'ttf, 'r> Phi<'ttf, 'r> {
-    fn new(events: Events, renderer: WindowCanvas, ttf_context: Sdl2TtfContext) -> Phi<'ttf, 'r> {
-        // We start with 32 mixer channels, which we may grow if necessary.
-        let allocated_channels = 32;
-        sdl2::mixer::allocate_channels(allocated_channels);
-        
-        Phi {
+    fn new(events: Events, mut renderer: WindowCanvas, ttf_context: Sdl2TtfContext,
+           audio: Audio, settings: Settings, seed: u64) -> Result<Phi<'ttf, 'r>> {
+        // From now on, SDL takes care of scaling everything we draw, and also
+        // maps the position of the mouse back to logical coordinates.
+        renderer.set_logical_size(LOGICAL_WIDTH, LOGICAL_HEIGHT)
+            .map_err(|err| PhiError::init("the renderer", err))?;
+
+        let mut phi = Phi {
             events,
             renderer,
+            assets: Assets::new(audio.is_enabled()),
+            audio,
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            startup_seed_used: false,
+            replay: Replay::Off,
+            settings,
+            fps: None,
             ttf_context,
-            allocated_channels,
             cached_fonts: HashMap::new(),
+            cached_text: TextCache::new(),
+        };
+
+        phi.apply_volumes();
+
+        if phi.settings.display_mode != display::DisplayMode::Windowed {
+            phi.apply_display();
+        }
+
+        Ok(phi)
+    }
+    
+    /// The seed with which `rng` was last initialized.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The seed from which a new run should be played. The first run is played
+    /// from the startup seed, i.e. that passed as `--seed` or recorded in the
+    /// replay, so that a seed saved along with a run plays it again. Every
+    /// later run draws its own seed from `rng`.
+    pub fn run_seed(&mut self) -> u64 {
+        if self.startup_seed_used {
+            self.rng.gen()
+        } else {
+            self.startup_seed_used = true;
+            self.seed
+        }
+    }
+
+    /// Restarts the random number generator from the given seed.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.seed = seed;
+    }
+    
+    /// Starts recording the inputs of every tick to a replay file at `path`.
+    /// The random number generator is restarted from the current seed, so
+    /// that this should be called before the first view is created.
+    pub fn record_replay<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let seed = self.seed;
+        self.replay = Replay::Recording(Recorder::create(path, seed)?);
+        self.reseed(seed);
+        Ok(())
+    }
+
+    /// Starts feeding the inputs recorded at `path` to the views instead of
+    /// those of the player, and restarts the random number generator from the
+    /// recorded seed. This should be called before the first view is created.
+    /// The game quits once the replay is over.
+    pub fn play_replay<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let playback = Playback::open(path)?;
+        self.reseed(playback.seed());
+        self.replay = Replay::Playing(playback);
+        Ok(())
+    }
+    
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Changes the settings, which take effect at once, except for `vsync`
+    /// which only does when the game starts. They are not saved until
+    /// `save_settings` is called.
+    pub fn set_settings(&mut self, settings: Settings) {
+        let display_changed = settings.display_mode != self.settings.display_mode
+            || settings.resolution != self.settings.resolution;
+
+        self.settings = settings;
+        self.apply_volumes();
+
+        if display_changed {
+            self.apply_display();
         }
     }
+
+    /// Writes the settings to disk, so that they are used from now on.
+    pub fn save_settings(&self) -> io::Result<()> {
+        self.settings.save(SETTINGS_PATH)
+    }
+
+    fn apply_volumes(&mut self) {
+        self.audio.set_music_volume(self.settings.music_volume);
+        self.audio.set_sfx_volume(self.settings.sfx_volume);
+    }
+
+    /// Switches to the display mode of the settings, then tells the views
+    /// that the size of the window changed, as SDL does not always do so.
+    fn apply_display(&mut self) {
+        let mode = self.settings.display_mode;
+
+        if let Err(err) = display::apply(self.renderer.window_mut(), mode, self.settings.resolution) {
+            println!("Could not change to the {} display mode: {}", mode.name(), err);
+        }
+
+        if let Ok(size) = self.renderer.output_size() {
+            self.events.notify_resize(size);
+        }
+    }
+
+    /// The resolutions which can be chosen for exclusive fullscreen, from the
+    /// largest to the smallest.
+    pub fn resolutions(&self) -> Vec<Resolution> {
+        display::resolutions(self.renderer.window())
+    }
     
+    /// The size of the screen in logical coordinates, which are the only
+    /// ones views should ever deal with.
     pub fn output_size(&self) -> (f64, f64) {
-        let (w, h) = self.renderer.output_size().unwrap();
+        let (w, h) = self.renderer.logical_size();
         (w as f64, h as f64)
     }
     
     /// Renders a string of text as a sprite using the provided parameters.
-    pub fn ttf_str_sprite(&mut self, text: &str, font_path: &'static str, size: u16, color: Color) -> Option<Sprite> {
-        //? First, we verify whether the font is already cached. If this is the
-        //? case, we use it to render the text.
-        if let Some(font) = self.cached_fonts.get(&(font_path, size)) {
-            return font.render(text).blended(color).ok()
-                .and_then(|surface| self.renderer.texture_creator().create_texture_from_surface(&surface).ok())
-                .map(Sprite::new)
-        }
-
-        //? Otherwise, we start by trying to load the requested font.
-        self.ttf_context.load_font(Path::new(font_path), size).ok()
-            .and_then(|font| {
-                //? If this works, we cache the font we acquired.
-                self.cached_fonts.insert((font_path, size), font);
-                //? Then, we call the method recursively. Because we know that
-                //? the font has been cached, the `if` block will be executed
-                //? and the sprite will be appropriately rendered.
-                self.ttf_str_sprite(text, font_path, size, color)
-            })
-    }
-    
-    /// Play a sound once, and allocate new channels if this is necessary.
-    pub fn play_sound(&mut self, sound: &::sdl2::mixer::Chunk) {
-        // Attempt to play the sound once.
-        match sdl2::mixer::Channel::all().play(sound, 0) {
-            Err(_) => {
-                // If there weren't enough channels allocated, then we double
-                // that number and try again.
-                self.allocated_channels *= 2;
-                sdl2::mixer::allocate_channels(self.allocated_channels);
-                self.play_sound(sound);
-                println!("Allocating {} channels.", self.allocated_channels);
-            },
+    /// The sprite is cached, so that rendering the same text again is cheap.
+    pub fn ttf_str_sprite(&mut self, text: &str, font_path: &'static str, size: u16, color: Color) -> Result<Sprite<'r>> {
+        let key = TextKey::new(text, font_path, size, color);
 
-            _ => { /* Everything's Alright! */ }
+        if let Some(sprite) = self.cached_text.get(&key) {
+            return Ok(sprite);
         }
+
+        self.load_font(font_path, size)?;
+
+        let failed = |reason: String| PhiError::Text { text: text.to_string(), reason };
+        let sprite = self.cached_fonts[&(font_path, size)].render(text).blended(color)
+            .map_err(|err| failed(err.to_string()))
+            .and_then(|surface| self.renderer.texture_creator()
+                .create_texture_from_surface(&surface)
+                .map_err(|err| failed(err.to_string())))
+            .map(Sprite::new)?;
+
+        self.cached_text.insert(key, sprite.clone());
+        Ok(sprite)
     }
 }
 
 /// A `ViewAction` is a way for the currently executed view to communicate with
 /// the game loop. It specifies whether an action should be executed before the
 /// next rendering.
+///
+/// The game loop keeps a stack of views, of which only the topmost one is
+/// updated.
 pub enum ViewAction {
-    Render(Box<dyn View>),
+    /// Keep running the current view.
+    None,
+    /// Run a new view on top of the current one, which is kept as it is until
+    /// the new view is popped.
+    Push(Box<dyn View>),
+    /// Throw the current view away and resume the one below it. The game
+    /// quits if there is none.
+    Pop,
+    /// Throw the current view away and run a new one in its place.
+    Replace(Box<dyn View>),
+    /// Throw every view of the stack away and run a new one.
+    ReplaceAll(Box<dyn View>),
     Quit,
 }
 
 pub trait View {
-    /// Called on every frame to take care of the logic of the program. From
-    /// user inputs and the instance's internal state, determine whether to
-    /// render itself or another view, close the window, etc.
+    /// Called on every simulation tick to take care of the logic of the
+    /// program. From user inputs and the instance's internal state, determine
+    /// whether to keep running, open or close other views, close the window,
+    /// etc.
+    ///
+    /// `elapsed` is expressed in seconds. It is always equal to the duration
+    /// of a tick, so that the game behaves the same on every machine.
     ///
-    /// `elapsed` is expressed in seconds.
-    fn update(self: Box<Self>, context: &mut Phi, elapsed: f64) -> ViewAction;
+    /// Errors, e.g. an asset which could not be loaded by the next view, are
+    /// reported to the player and end the game.
+    fn update(&mut self, context: &mut Phi, elapsed: f64) -> Result<ViewAction>;
 
     /// Called on every frame to take care rendering the current view. It
     /// disallows mutating the object by default, although you may still do it
     /// through a `RefCell` if you need to.
-    fn render(&self, context: &mut Phi);
+    ///
+    /// `alpha`, in [0, 1), tells how far we are between the last tick and the
+    /// next one. Moving objects should be drawn that far between their
+    /// previous and their current position.
+    fn render(&self, context: &mut Phi, alpha: f64);
+
+    /// Whether the view only covers part of the screen, e.g. a pause menu or
+    /// a dialog, in which case the view below it is rendered first. That view
+    /// is frozen, as it is not updated.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// Renders the views of the `stack` which can be seen, from the bottom up.
+fn render_stack(context: &mut Phi, stack: &[Box<dyn View>], alpha: f64) {
+    // Find the lowest view which is visible, i.e. the topmost one which is
+    // not an overlay.
+    let first = stack.iter()
+        .rposition(|view| !view.is_overlay())
+        .unwrap_or(0);
+
+    for (i, view) in stack.iter().enumerate().skip(first) {
+        // The views below the top one are not updated anymore, so we render
+        // them exactly where their last tick left them.
+        let alpha = if i + 1 == stack.len() { alpha } else { 1.0 };
+        view.render(context, alpha);
+    }
+}
+
+/// Draws the frame rate and the seed of the random number generator in the
+/// top-right corner of the screen, over the views, if the player asked for it.
+fn render_debug(context: &mut Phi) {
+    if !context.settings.show_fps {
+        return;
+    }
+
+    let lines = [
+        match context.fps {
+            Some(fps) => format!("{} FPS", fps),
+            None => "-- FPS".to_string(),
+        },
+        format!("Seed: {}", context.seed),
+    ];
+
+    let (win_w, _) = context.output_size();
+    let mut y = 4.0;
+
+    for line in &lines {
+        // This is only a debugging aid, which is not worth failing for.
+        if let Ok(sprite) = context.ttf_str_sprite(line, DEBUG_FONT, 16, Color::RGB(255, 255, 0)) {
+            let (w, h) = sprite.size();
+            context.renderer.copy_sprite(&sprite, data::Rectangle { x: win_w - w - 4.0, y, w, h });
+            y += h;
+        }
+    }
 }
 
-pub fn spawn<F>(title: &str, init: F)
-where F: Fn(&mut Phi) -> Box<dyn View> {
+/// Goes to the next display mode if the player pressed the hotkey, whichever
+/// view is running, and remembers it for the next games.
+fn toggle_fullscreen(context: &mut Phi) {
+    if !context.events.now.pressed(Action::ToggleFullscreen) {
+        return;
+    }
+
+    let mut settings = context.settings.clone();
+    settings.display_mode = settings.display_mode.next();
+    context.set_settings(settings);
+
+    if let Err(err) = context.save_settings() {
+        println!("Could not save the settings: {}", err);
+    }
+}
+
+/// Returns the value following `name` on the command line, if any.
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// Returns the seed passed on the command line as `--seed <number>`, if any,
+/// and otherwise one derived from the current time.
+fn startup_seed() -> u64 {
+    arg_value("--seed")
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|time| time.as_nanos() as u64)
+                .unwrap_or(0)
+        })
+}
+
+/// The number of times per second that the views are updated, unless the game
+/// asks otherwise through `PhiConfig`.
+const TICKS_PER_SECOND: f64 = 60.0;
+
+/// If a frame takes longer than this many seconds, e.g. because the window
+/// was being dragged, we pretend it didn't. Otherwise, we would have to run so
+/// many ticks to catch up that the next frame would take even longer.
+const MAX_FRAME_TIME: f64 = 0.25;
+
+/// Converts the real time elapsed between frames into a whole number of
+/// fixed-length simulation ticks.
+pub struct FixedStep {
+    tick: f64,
+    accumulator: f64,
+}
+
+impl FixedStep {
+    pub fn new(ticks_per_second: f64) -> FixedStep {
+        FixedStep {
+            tick: 1.0 / ticks_per_second,
+            accumulator: 0.0,
+        }
+    }
+
+    /// The duration of a tick, in seconds.
+    pub fn tick(&self) -> f64 {
+        self.tick
+    }
+
+    /// Adds the time, in seconds, that has elapsed since the last frame.
+    pub fn add_time(&mut self, elapsed: f64) {
+        self.accumulator += elapsed.min(MAX_FRAME_TIME);
+    }
+
+    /// Returns `true` and consumes a tick from the accumulated time if there
+    /// is one left to simulate, and returns `false` otherwise.
+    pub fn next_tick(&mut self) -> bool {
+        if self.accumulator >= self.tick {
+            self.accumulator -= self.tick;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// How far we are between the last tick and the next one, in [0, 1).
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / self.tick
+    }
+}
+
+/// Runs the game, starting with the view built by `init`, until a view asks
+/// to quit. Fails if SDL could not be started, or if a view could not be
+/// built, e.g. because an asset is missing.
+///
+/// This uses the default `PhiConfig`. Use a `PhiBuilder` to change it.
+pub fn spawn<F>(title: &str, init: F) -> Result<()>
+where F: Fn(&mut Phi) -> Result<Box<dyn View>> {
+    PhiBuilder::new(title, init).run()
+}
+
+/// Runs the game as configured by `config`, see `spawn`.
+fn run<F>(config: &PhiConfig, init: F) -> Result<()>
+where F: Fn(&mut Phi) -> Result<Box<dyn View>> {
     // Initialize SDL2
-    let sdl_context = sdl2::init().unwrap();
-    let video = sdl_context.video().unwrap();
-    let mut timer = sdl_context.timer().unwrap();
-    let _image_context = sdl2::image::init(sdl2::image::InitFlag::PNG).unwrap();
-    let _ttf_context = sdl2::ttf::init().unwrap();
+    let sdl_context = sdl2::init().map_err(|err| PhiError::init("SDL", err))?;
+    let video = sdl_context.video().map_err(|err| PhiError::init("the video subsystem", err))?;
+    let mut timer = sdl_context.timer().map_err(|err| PhiError::init("the timer", err))?;
+    let _image_context = sdl2::image::init(sdl2::image::InitFlag::PNG)
+        .map_err(|err| PhiError::init("SDL_image", err))?;
+    let _ttf_context = sdl2::ttf::init().map_err(|err| PhiError::init("SDL_ttf", err))?;
     
     // Initialize audio plugin
-    let _mixer_context = sdl2::mixer::init(sdl2::mixer::InitFlag::OGG).unwrap();
-    sdl2::mixer::open_audio(44100, sdl2::mixer::AUDIO_S16LSB, 2, 1024).unwrap();
+    let mixer_context = audio::open(&config.audio);
     
+    let settings = Settings::load_or(SETTINGS_PATH, config.default_settings());
+
     // Create the window
-    let window = video.window(title, 800, 600)
-        .position_centered().opengl().resizable()
-        .build().unwrap();
+    let (width, height) = config.window_size;
+    let mut window = video.window(&config.title, width, height);
+    window.position_centered();
+
+    if config.opengl {
+        window.opengl();
+    }
+
+    if config.resizable {
+        window.resizable();
+    }
+
+    let window = window.build()
+        .map_err(|err| PhiError::init("the window", err))?;
+
+    let mut canvas = window.into_canvas().accelerated();
+
+    if config.vsync || settings.vsync {
+        canvas = canvas.present_vsync();
+    }
 
     // Create the context
     let mut context = Phi::new(
-        Events::new(sdl_context.event_pump().unwrap()),
-        window.into_canvas().accelerated().build().unwrap(),
-        _ttf_context);
+        Events::new(
+            sdl_context.event_pump().map_err(|err| PhiError::init("the event pump", err))?,
+            Bindings::load_or_default(BINDINGS_PATH),
+            sdl_context.game_controller().ok()),
+        canvas.build()
+            .map_err(|err| PhiError::init("the renderer", err))?,
+        _ttf_context,
+        Audio::new(mixer_context.is_some(), config.audio.voices),
+        settings,
+        startup_seed())?;
+
+    // Record or play a replay if asked to on the command line
+    if let Some(path) = arg_value("--record") {
+        if let Err(err) = context.record_replay(&path) {
+            println!("Could not record the replay to {}: {}", path, err);
+        }
+    }
+
+    if let Some(path) = arg_value("--replay") {
+        if let Err(err) = context.play_replay(&path) {
+            println!("Could not play the replay {}: {}", path, err);
+        }
+    }
+
+    println!("Seed: {}", context.seed());
 
     // Create the default view
-    let mut current_view = init(&mut context);
+    let mut stack = vec![init(&mut context)?];
 
 
     // Frame timing
 
-    let interval = 1_000 / 60;
-    let mut before = timer.ticks();
+    let mut clock = FixedStep::new(config.ticks_per_second);
+    let frequency = timer.performance_frequency() as f64;
+    let mut before = timer.performance_counter();
     let mut last_second = timer.ticks();
     let mut fps = 0u16;
 
     loop {
         // Frame timing (bis)
 
-        let now = timer.ticks();
-        let dt = now - before;
-        let elapsed = dt as f64 / 1_000.0;
-
-        // If the time elapsed since the last frame is too small, wait out the
-        // difference and try again.
-        if dt < interval {
-            timer.delay(interval - dt);
-            continue;
-        }
-
+        let now = timer.performance_counter();
+        let elapsed = (now - before) as f64 / frequency;
         before = now;
         fps += 1;
 
-        if now - last_second > 1_000 {
-            println!("FPS: {}", fps);
-            last_second = now;
+        if timer.ticks() - last_second > 1_000 {
+            println!("FPS: {} (seed: {}, voices: {}/{})", fps, context.seed(),
+                context.audio.active_voices().len(), context.audio.voice_capacity());
+            context.fps = Some(fps);
+            last_second = timer.ticks();
             fps = 0;
         }
 
         // Logic & rendering
 
-        context.events.pump(&mut context.renderer);
+        if !run_frame(&mut context, &mut stack, &mut clock, elapsed)? {
+            return Ok(());
+        }
+
+        // If the frame was faster than we need, sleep for the remaining time
+        // rather than drawing frames that nobody will see.
+        let max_fps = context.settings.max_fps;
+
+        if max_fps > 0 {
+            let interval = 1.0 / max_fps as f64;
+            let spent = (timer.performance_counter() - now) as f64 / frequency;
+
+            if spent < interval {
+                timer.delay(((interval - spent) * 1_000.0) as u32);
+            }
+        }
+    }
+}
+
+/// Adds `elapsed` seconds to the `clock`, updates the view at the top of the
+/// `stack` once for every tick that is due, then renders the visible views.
+/// Returns `false` if the game should quit, or the error of a view which
+/// failed. This is shared by `spawn` and the
+/// headless backend, so that both drive the views in exactly the same way.
+fn run_frame(context: &mut Phi, stack: &mut Vec<Box<dyn View>>, clock: &mut FixedStep, elapsed: f64)
+-> Result<bool> {
+    clock.add_time(elapsed);
+
+    // We only pump the events once a tick is due, otherwise key presses
+    // happening during a frame without ticks would never reach the views.
+    let mut pumped = false;
+
+    while clock.next_tick() {
+        if pumped {
+            // Key presses must only be reported on the first tick of a frame,
+            // or they would be handled several times.
+            context.events.now = ImmediateEvents::new();
+        } else {
+            context.events.pump(&mut context.renderer);
+            pumped = true;
+            toggle_fullscreen(context);
+        }
+
+        // When playing a replay, the recorded inputs and tick duration
+        // replace the live ones.
+        let elapsed = match context.replay.tick(&mut context.events, clock.tick()) {
+            Some(elapsed) => elapsed,
+            None => return Ok(false),
+        };
 
-        match current_view.update(&mut context, elapsed) {
-            ViewAction::Render(view) => {
-                current_view = view;
-                current_view.render(&mut context);
-                context.renderer.present();
+        let action = match stack.last_mut() {
+            Some(view) => view.update(context, elapsed)?,
+            None => return Ok(false),
+        };
+
+        match action {
+            ViewAction::None => {},
+
+            ViewAction::Push(view) =>
+                stack.push(view),
+
+            ViewAction::Pop => {
+                stack.pop();
+
+                if stack.is_empty() {
+                    return Ok(false);
+                }
+            },
+
+            ViewAction::Replace(view) => {
+                stack.pop();
+                stack.push(view);
+            },
+
+            ViewAction::ReplaceAll(view) => {
+                stack.clear();
+                stack.push(view);
             },
 
             ViewAction::Quit =>
-                break,
+                return Ok(false),
         }
     }
+
+    context.audio.update();
+    render_stack(context, stack, clock.alpha());
+    render_debug(context);
+    context.renderer.present();
+    Ok(true)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_ticks(clock: &mut FixedStep) -> usize {
+        let mut ticks = 0;
+
+        while clock.next_tick() {
+            ticks += 1;
+        }
+
+        ticks
+    }
+
+    #[test]
+    fn fixed_step_runs_whole_ticks() {
+        let mut clock = FixedStep::new(8.0);
+        assert_eq!(clock.tick(), 0.125);
+
+        clock.add_time(0.0625);
+        assert_eq!(count_ticks(&mut clock), 0);
+        assert_eq!(clock.alpha(), 0.5);
+
+        // The leftover time carries over to the next frame.
+        clock.add_time(0.1875);
+        assert_eq!(count_ticks(&mut clock), 2);
+        assert_eq!(clock.alpha(), 0.0);
+    }
+
+    #[test]
+    fn events_round_trip_through_replays() {
+        let mut headless = headless::testing::start_blank();
+        let events = &mut headless.phi().events;
+
+        // Go through every state an action may be in, and leave `quit` alone.
+        let word: String = "PR10".chars().cycle().take(Action::ALL.len()).collect();
+        let line = format!("{}0 0.5 -0.25 10 20 P1R 1 -2", word);
+
+        assert_eq!(events.decode(&line), Some(()));
+        assert_eq!(events.encode(), line);
+        assert!(events.now.pressed(Action::ALL[0]));
+        assert!(events.now.released(Action::ALL[1]));
+        assert!(events.is_down(Action::ALL[2]));
+        assert_eq!(events.now.wheel, (1, -2));
+    }
+
+    #[test]
+    fn events_reject_malformed_lines() {
+        let mut headless = headless::testing::start_blank();
+        let events = &mut headless.phi().events;
+        let word = "0".repeat(Action::ALL.len() + 1);
+
+        assert_eq!(events.decode(&format!("{} 0 0 0 0 000 0 0", word)), Some(()));
+        assert_eq!(events.decode(""), None);
+        assert_eq!(events.decode(&format!("{}0 0 0 0 0 000 0 0", word)), None);
+        assert_eq!(events.decode(&format!("{} 0 0 0 0 000 0 0 0", word)), None);
+        assert_eq!(events.decode(&format!("{} 0 x 0 0 000 0 0", word)), None);
+        assert_eq!(events.decode(&format!("{} 0 0 0 0 0X0 0 0", word)), None);
+    }
+
+    #[test]
+    fn first_run_keeps_the_startup_seed() {
+        let mut headless = headless::testing::start_blank();
+        let phi = headless.phi();
+
+        let first = phi.run_seed();
+        assert_eq!(first, 0);
+        phi.reseed(first);
+
+        let second = phi.run_seed();
+        assert_ne!(second, first);
+        phi.reseed(second);
+        assert_ne!(phi.run_seed(), second);
+    }
+
+    #[test]
+    fn unplugging_a_controller_releases_its_buttons() {
+        let mut headless = headless::testing::start_blank();
+        let events = &mut headless.phi().events;
+
+        events.press_button(1, sdl2::controller::Button::A);
+        events.press_button(1, sdl2::controller::Button::DPadUp);
+        events.press_button(2, sdl2::controller::Button::B);
+        events.press_button(2, sdl2::controller::Button::DPadUp);
+        assert!(events.is_down(Action::Fire));
+        assert!(events.is_down(Action::Weapon3));
+
+        events.close_controller(1);
+        assert!(!events.is_down(Action::Fire));
+        assert!(events.is_down(Action::Weapon3));
+        // The other controller still holds it.
+        assert!(events.is_down(Action::MoveUp));
+
+        events.close_controller(2);
+        assert!(!events.is_down(Action::Weapon3));
+        assert!(!events.is_down(Action::MoveUp));
+
+        // Releasing buttons of a controller which is gone changes nothing.
+        events.release_button(1, sdl2::controller::Button::A);
+        assert!(!events.is_down(Action::Fire));
+    }
+
+    #[test]
+    fn fixed_step_caps_long_frames() {
+        let mut clock = FixedStep::new(8.0);
+
+        clock.add_time(10.0);
+        assert_eq!(count_ticks(&mut clock), (MAX_FRAME_TIME / 0.125) as usize);
+    }
 }
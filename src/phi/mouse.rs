@@ -0,0 +1,51 @@
+use crate::phi::{Events, ImmediateEvents};
+use sdl2::mouse::MouseButton;
+
+
+/// The index of `button` in the arrays which hold the state of the mouse, or
+/// `None` if we do not keep track of it.
+fn button_index(button: MouseButton) -> Option<usize> {
+    match button {
+        MouseButton::Left => Some(0),
+        MouseButton::Middle => Some(1),
+        MouseButton::Right => Some(2),
+        _ => None,
+    }
+}
+
+impl Events {
+    /// Called whenever a button of the mouse goes up or down.
+    pub(super) fn mouse_button(&mut self, button: MouseButton, down: bool) {
+        if let Some(i) = button_index(button) {
+            // Pressing a button which is already held does not count.
+            if self.mouse_held[i] != down {
+                self.now.mouse_buttons[i] = Some(down);
+            }
+
+            self.mouse_held[i] = down;
+        }
+    }
+
+    /// The position of the pointer in logical coordinates. If it is over the
+    /// black bars around the picture, it may be outside of the screen.
+    pub fn mouse_pos(&self) -> (f64, f64) {
+        (self.mouse.0 as f64, self.mouse.1 as f64)
+    }
+
+    /// Whether `button` is currently held down.
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        button_index(button).map_or(false, |i| self.mouse_held[i])
+    }
+}
+
+impl ImmediateEvents {
+    /// Whether `button` started being held since the last frame.
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        button_index(button).map_or(false, |i| self.mouse_buttons[i] == Some(true))
+    }
+
+    /// Whether `button` stopped being held since the last frame.
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        button_index(button).map_or(false, |i| self.mouse_buttons[i] == Some(false))
+    }
+}
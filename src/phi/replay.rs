@@ -0,0 +1,139 @@
+use crate::phi::Events;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+
+
+/// Bumped whenever the layout of replay files changes, so that we refuse to
+/// play files we would misinterpret.
+const REPLAY_VERSION: u32 = 4;
+
+/// A replay is a text file which starts with a header:
+///
+///     phi-replay <version>
+///     seed <seed>
+///     inputs <name> <name> ...
+///
+/// followed by one line for every tick, holding the tick's duration and the
+/// state of the inputs as encoded by `Events::encode`, separated by a space.
+pub enum Replay {
+    Off,
+    Recording(Recorder),
+    Playing(Playback),
+}
+
+impl Replay {
+    /// Called right before every tick, once the events have been pumped.
+    /// When recording, stores the state of `events`; when playing, replaces
+    /// it by the recorded one.
+    ///
+    /// Returns the duration of the tick, which is `elapsed` unless we are
+    /// playing, or `None` once the replay is over.
+    pub fn tick(&mut self, events: &mut Events, elapsed: f64) -> Option<f64> {
+        match *self {
+            Replay::Off =>
+                Some(elapsed),
+
+            Replay::Recording(ref mut recorder) => {
+                if let Err(err) = recorder.record(events, elapsed) {
+                    println!("Stopped recording the replay: {}", err);
+                    *self = Replay::Off;
+                }
+
+                Some(elapsed)
+            },
+
+            Replay::Playing(ref mut playback) => {
+                let elapsed = playback.next(events);
+
+                if elapsed.is_none() {
+                    println!("The replay is over.");
+                }
+
+                elapsed
+            },
+        }
+    }
+}
+
+/// Writes the state of the inputs to a replay file, tick after tick.
+pub struct Recorder {
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Creates the replay file at `path` and writes its header.
+    pub fn create<P: AsRef<Path>>(path: P, seed: u64) -> io::Result<Recorder> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        writeln!(out, "phi-replay {}", REPLAY_VERSION)?;
+        writeln!(out, "seed {}", seed)?;
+        writeln!(out, "inputs {}", Events::input_names().join(" "))?;
+
+        Ok(Recorder { out })
+    }
+
+    pub fn record(&mut self, events: &Events, elapsed: f64) -> io::Result<()> {
+        writeln!(self.out, "{} {}", elapsed, events.encode())
+    }
+}
+
+/// Reads a replay file, tick after tick.
+pub struct Playback {
+    seed: u64,
+    lines: Lines<BufReader<File>>,
+}
+
+impl Playback {
+    /// Opens the replay file at `path` and checks that its header matches
+    /// this version of the game.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Playback> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+
+        let mut header = |key: &str| -> io::Result<String> {
+            let line = lines.next().unwrap_or_else(|| Ok(String::new()))?;
+
+            if line.starts_with(key) && line[key.len()..].starts_with(' ') {
+                Ok(line[key.len() + 1..].to_string())
+            } else {
+                Err(invalid(format!("expected `{}` in the header", key)))
+            }
+        };
+
+        let version = header("phi-replay")?;
+        if version != REPLAY_VERSION.to_string() {
+            return Err(invalid(format!(
+                "version {} is not supported (expected {})", version, REPLAY_VERSION)));
+        }
+
+        let seed = header("seed")?.parse()
+            .map_err(|_| invalid("the seed is not a number".to_string()))?;
+
+        if header("inputs")? != Events::input_names().join(" ") {
+            return Err(invalid("the inputs do not match this version of the game".to_string()));
+        }
+
+        Ok(Playback { seed, lines })
+    }
+
+    /// The seed with which the replay was recorded.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Loads the state of the next tick into `events` and returns its
+    /// duration, or `None` if there is no tick left or it could not be read.
+    pub fn next(&mut self, events: &mut Events) -> Option<f64> {
+        let line = self.lines.next()?.ok()?;
+        let mut words = line.splitn(2, ' ');
+
+        let elapsed = words.next()?.parse().ok()?;
+        events.decode(words.next()?)?;
+
+        Some(elapsed)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
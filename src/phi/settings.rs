@@ -0,0 +1,203 @@
+use crate::phi::display::{DisplayMode, Resolution};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+
+/// The options the player may change at runtime. They are stored in a text
+/// file with one `key = value` line per option:
+///
+///     music_volume = 0.8
+///     display_mode = exclusive
+///     resolution = 1920x1080@60
+///     max_fps = 0
+///
+/// Options which are missing or cannot be read keep their default value, i.e.
+/// that given to `parse`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// In [0, 1].
+    pub music_volume: f64,
+    /// In [0, 1].
+    pub sfx_volume: f64,
+    pub display_mode: DisplayMode,
+    /// The resolution of the screen in exclusive fullscreen, or `None` to
+    /// keep that of the desktop.
+    pub resolution: Option<Resolution>,
+    /// Only taken into account when the game starts.
+    pub vsync: bool,
+    /// How many frames per second we draw at most, or 0 for no limit. Past
+    /// the refresh rate of the screen, more frames only burn CPU time.
+    pub max_fps: u32,
+    /// Whether the frame rate and the seed of the run are drawn in a corner
+    /// of the screen.
+    pub show_fps: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            display_mode: DisplayMode::Windowed,
+            resolution: None,
+            vsync: false,
+            max_fps: 120,
+            show_fps: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings from the content of a settings file, on top of
+    /// `defaults`.
+    pub fn parse(source: &str, defaults: Settings) -> Settings {
+        let mut settings = defaults;
+
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut sides = line.splitn(2, '=');
+            let key = sides.next().unwrap().trim();
+            let value = sides.next().map(|value| value.trim()).unwrap_or("");
+
+            let parsed = match key {
+                "music_volume" => value.parse().map(|v| settings.music_volume = v).is_ok(),
+                "sfx_volume" => value.parse().map(|v| settings.sfx_volume = v).is_ok(),
+                "display_mode" => DisplayMode::from_name(value).map(|v| settings.display_mode = v).is_some(),
+                "resolution" if value == "desktop" => { settings.resolution = None; true },
+                "resolution" => Resolution::parse(value).map(|v| settings.resolution = Some(v)).is_some(),
+                "vsync" => value.parse().map(|v| settings.vsync = v).is_ok(),
+                "max_fps" => value.parse().map(|v| settings.max_fps = v).is_ok(),
+                "show_fps" => value.parse().map(|v| settings.show_fps = v).is_ok(),
+                _ => false,
+            };
+
+            if !parsed {
+                println!("Skipped line {} of the settings: `{}`", i + 1, line);
+            }
+        }
+
+        settings.music_volume = settings.music_volume.max(0.0).min(1.0);
+        settings.sfx_volume = settings.sfx_volume.max(0.0).min(1.0);
+        settings
+    }
+
+    /// Reads the settings from the file at `path`, falling back to `defaults`
+    /// if it is missing or cannot be read.
+    pub fn load_or<P: AsRef<Path>>(path: P, defaults: Settings) -> Settings {
+        match fs::read_to_string(&path) {
+            Ok(source) => Settings::parse(&source, defaults),
+
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound =>
+                defaults,
+
+            Err(err) => {
+                println!("Could not read the settings from {}: {}",
+                    path.as_ref().display(), err);
+                defaults
+            },
+        }
+    }
+
+    /// Writes the settings to the file at `path`. As with the high scores,
+    /// they are first written alongside it, then moved in place, so that the
+    /// previous settings are kept whole if we fail midway.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+
+        {
+            let mut out = io::BufWriter::new(fs::File::create(&tmp)?);
+
+            writeln!(out, "music_volume = {}", self.music_volume)?;
+            writeln!(out, "sfx_volume = {}", self.sfx_volume)?;
+            writeln!(out, "display_mode = {}", self.display_mode.name())?;
+
+            match self.resolution {
+                Some(resolution) => writeln!(out, "resolution = {}", resolution)?,
+                None => writeln!(out, "resolution = desktop")?,
+            }
+
+            writeln!(out, "vsync = {}", self.vsync)?;
+            writeln!(out, "max_fps = {}", self.max_fps)?;
+            writeln!(out, "show_fps = {}", self.show_fps)?;
+
+            out.flush()?;
+        }
+
+        fs::rename(&tmp, path)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keeps_defaults_for_bad_lines() {
+        let settings = Settings::parse("
+            # Comments and blank lines are skipped.
+
+            music_volume = 1.5
+            sfx_volume = loud
+            vsync = true
+            max_fps = 60
+            unknown = 1
+        ", Settings::default());
+
+        assert_eq!(settings, Settings {
+            music_volume: 1.0,
+            vsync: true,
+            max_fps: 60,
+            ..Settings::default()
+        });
+    }
+
+    #[test]
+    fn missing_settings_keep_the_given_defaults() {
+        let defaults = Settings { max_fps: 60, ..Settings::default() };
+        assert_eq!(Settings::parse("vsync = true", defaults.clone()).max_fps, 60);
+
+        let path = std::env::temp_dir().join(format!("phi-missing-{}.cfg", std::process::id()));
+        assert_eq!(Settings::load_or(&path, defaults.clone()), defaults);
+    }
+
+    #[test]
+    fn parse_reads_display_modes() {
+        let settings = Settings::parse("display_mode = exclusive\nresolution = 1920x1080@60",
+                                       Settings::default());
+        assert_eq!(settings.display_mode, DisplayMode::Exclusive);
+        assert_eq!(settings.resolution, Some(Resolution { w: 1920, h: 1080, refresh_rate: 60 }));
+
+        assert_eq!(Settings::parse("resolution = desktop", Settings::default()).resolution, None);
+        assert_eq!(Settings::parse("resolution = 1920x", Settings::default()).resolution, None);
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let settings = Settings {
+            music_volume: 0.7,
+            sfx_volume: 0.0,
+            vsync: true,
+            max_fps: 0,
+            show_fps: true,
+            display_mode: DisplayMode::Exclusive,
+            resolution: Some(Resolution { w: 1280, h: 720, refresh_rate: 144 }),
+        };
+
+        let path = std::env::temp_dir().join(format!("phi-settings-{}.cfg", std::process::id()));
+        settings.save(&path).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+        let source = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(source.contains("music_volume = 0.7\n"));
+        assert_eq!(Settings::parse(&source, Settings::default()), settings);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::phi::Phi;
+use crate::phi::data::Rectangle;
+use crate::phi::error::{PhiError, Result};
+use crate::phi::gfx::{Renderable, Sprite};
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+use std::collections::HashMap;
+use std::path::Path;
+
+
+/// How many rendered strings are kept around. Past that, the ones which were
+/// used the least recently are thrown away.
+const TEXT_CACHE_CAPACITY: usize = 256;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(super) struct TextKey {
+    text: String,
+    font: &'static str,
+    size: u16,
+    color: (u8, u8, u8, u8),
+}
+
+impl TextKey {
+    pub(super) fn new(text: &str, font: &'static str, size: u16, color: Color) -> TextKey {
+        TextKey {
+            text: text.to_string(),
+            font: font,
+            size: size,
+            color: color.rgba(),
+        }
+    }
+}
+
+/// Remembers the sprites rendered by `Phi::ttf_str_sprite`, so that drawing
+/// the same string again, e.g. a counter which only changes once in a while,
+/// does not create a new texture every time.
+pub(super) struct TextCache<'r> {
+    /// The sprites, along with the value of `clock` when they were last used.
+    entries: HashMap<TextKey, (Sprite<'r>, u64)>,
+    clock: u64,
+}
+
+impl<'r> TextCache<'r> {
+    pub(super) fn new() -> TextCache<'r> {
+        TextCache {
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub(super) fn get(&mut self, key: &TextKey) -> Option<Sprite<'r>> {
+        self.clock += 1;
+        let clock = self.clock;
+
+        self.entries.get_mut(key).map(|entry| {
+            entry.1 = clock;
+            entry.0.clone()
+        })
+    }
+
+    pub(super) fn insert(&mut self, key: TextKey, sprite: Sprite<'r>) {
+        if self.entries.len() >= TEXT_CACHE_CAPACITY {
+            let oldest = self.entries.iter()
+                .min_by_key(|&(_, &(_, used))| used)
+                .map(|(key, _)| key.clone());
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.entries.insert(key, (sprite, self.clock));
+    }
+}
+
+
+/// Where the lines of a `TextBlock` are placed relative to one another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    /// How far from the left of a block `block_w` wide a line `w` wide goes.
+    pub(super) fn offset(self, block_w: f64, w: f64) -> f64 {
+        match self {
+            Align::Left => 0.0,
+            Align::Center => (block_w - w) / 2.0,
+            Align::Right => block_w - w,
+        }
+    }
+}
+
+/// Some text laid out on several lines, ready to be drawn.
+pub struct TextBlock<'r> {
+    /// The sprites making up the text, i.e. whole lines or single glyphs,
+    /// along with where they go relative to the top-left corner of the block.
+    pieces: Vec<(Sprite<'r>, Rectangle)>,
+    size: (f64, f64),
+}
+
+impl<'r> TextBlock<'r> {
+    pub(super) fn new(pieces: Vec<(Sprite<'r>, Rectangle)>, size: (f64, f64)) -> TextBlock<'r> {
+        TextBlock {
+            pieces: pieces,
+            size: size,
+        }
+    }
+
+    /// The dimensions of the block, i.e. those of its widest line by the
+    /// height of all of its lines.
+    pub fn size(&self) -> (f64, f64) {
+        self.size
+    }
+}
+
+impl<'r> Renderable for TextBlock<'r> {
+    fn render(&self, renderer: &mut WindowCanvas, dest: Rectangle) {
+        // Stretch the block to fit `dest`, as we would do with a sprite.
+        let (w, h) = self.size;
+        let (scale_x, scale_y) = (dest.w / w, dest.h / h);
+
+        for &(ref sprite, rect) in &self.pieces {
+            sprite.render(renderer, Rectangle {
+                x: dest.x + rect.x * scale_x,
+                y: dest.y + rect.y * scale_y,
+                w: rect.w * scale_x,
+                h: rect.h * scale_y,
+            });
+        }
+    }
+}
+
+impl<'ttf, 'r> Phi<'ttf, 'r> {
+    /// Loads the font at `path` with the given size, unless it was already.
+    pub(super) fn load_font(&mut self, path: &'static str, size: u16) -> Result<()> {
+        if !self.cached_fonts.contains_key(&(path, size)) {
+            let font = self.ttf_context.load_font(Path::new(path), size)
+                .map_err(|reason| PhiError::asset(path, reason))?;
+
+            self.cached_fonts.insert((path, size), font);
+        }
+
+        Ok(())
+    }
+
+    /// The size the given text would have if it was rendered on a single line.
+    pub fn text_size(&mut self, text: &str, font_path: &'static str, size: u16) -> Result<(f64, f64)> {
+        self.load_font(font_path, size)?;
+
+        self.cached_fonts[&(font_path, size)].size_of(text)
+            .map(|(w, h)| (w as f64, h as f64))
+            .map_err(|err| PhiError::Text { text: text.to_string(), reason: err.to_string() })
+    }
+
+    /// Lays `text` out on as many lines as needed, so that none is wider than
+    /// `max_width` if there is one. See `wrap_lines` for where lines break.
+    pub fn layout_text(&mut self, text: &str, font_path: &'static str, size: u16, color: Color,
+                       max_width: Option<f64>, align: Align) -> Result<TextBlock<'r>> {
+        self.load_font(font_path, size)?;
+        let line_h = self.cached_fonts[&(font_path, size)].recommended_line_spacing() as f64;
+
+        let lines = wrap_lines(text, max_width,
+            |line| Ok(self.text_size(line, font_path, size)?.0))?;
+
+        let mut sprites = Vec::with_capacity(lines.len());
+
+        for (i, line) in lines.iter().enumerate() {
+            // SDL_ttf refuses to render empty strings, and blank lines have
+            // nothing to draw anyway.
+            if !line.is_empty() {
+                sprites.push((i, self.ttf_str_sprite(line, font_path, size, color)?));
+            }
+        }
+
+        let block_w = sprites.iter()
+            .map(|&(_, ref sprite)| sprite.size().0)
+            .fold(0.0, f64::max);
+
+        let pieces = sprites.into_iter()
+            .map(|(i, sprite)| {
+                let (w, h) = sprite.size();
+                let x = align.offset(block_w, w);
+                (sprite, Rectangle { x: x, y: line_h * i as f64, w: w, h: h })
+            })
+            .collect();
+
+        Ok(TextBlock::new(pieces, (block_w, line_h * lines.len() as f64)))
+    }
+}
+
+/// Breaks `text` into lines no wider than `max_width`, if there is one,
+/// according to `width`, which measures a line. Lines are broken between
+/// words, and at every newline. A word too long to fit is left on a line of
+/// its own.
+pub(super) fn wrap_lines<F>(text: &str, max_width: Option<f64>, mut width: F) -> Result<Vec<String>>
+    where F: FnMut(&str) -> Result<f64>
+{
+    let mut lines = vec![];
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+
+        for word in paragraph.split_whitespace() {
+            let candidate =
+                if line.is_empty() { word.to_string() }
+                else { format!("{} {}", line, word) };
+
+            let fits = match max_width {
+                Some(max_width) => width(&candidate)? <= max_width,
+                None => true,
+            };
+
+            if fits || line.is_empty() {
+                line = candidate;
+            } else {
+                lines.push(line);
+                line = word.to_string();
+            }
+        }
+
+        lines.push(line);
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phi::headless::testing;
+
+    fn chars(line: &str) -> Result<f64> {
+        Ok(line.chars().count() as f64)
+    }
+
+    #[test]
+    fn wrap_lines_between_words() {
+        assert_eq!(wrap_lines("one two three", Some(7.0), chars).unwrap(),
+                   vec!["one two", "three"]);
+    }
+
+    #[test]
+    fn wrap_lines_leaves_long_words_alone() {
+        assert_eq!(wrap_lines("a overlong b", Some(4.0), chars).unwrap(),
+                   vec!["a", "overlong", "b"]);
+        assert_eq!(wrap_lines("overlong", Some(4.0), chars).unwrap(), vec!["overlong"]);
+    }
+
+    #[test]
+    fn wrap_lines_keeps_blank_lines() {
+        assert_eq!(wrap_lines("one\n\ntwo", Some(10.0), chars).unwrap(),
+                   vec!["one", "", "two"]);
+        assert_eq!(wrap_lines("", Some(10.0), chars).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn wrap_lines_without_max_width() {
+        let lines = wrap_lines("one two three\nfour", None, |_| panic!("measured a line"));
+        assert_eq!(lines.unwrap(), vec!["one two three", "four"]);
+    }
+
+    #[test]
+    fn text_cache_evicts_the_least_recently_used() {
+        let mut headless = testing::start_blank();
+        let creator = headless.phi().renderer.texture_creator();
+        let sprite = Sprite::new(creator.create_texture_target(None, 1, 1).unwrap());
+
+        let key = |i: usize| TextKey::new(&i.to_string(), "font.ttf", 12, Color::RGB(0, 0, 0));
+        let mut cache = TextCache::new();
+
+        for i in 0..TEXT_CACHE_CAPACITY {
+            cache.insert(key(i), sprite.clone());
+        }
+
+        // Using the oldest entry makes the next one the least recently used.
+        assert!(cache.get(&key(0)).is_some());
+        cache.insert(key(TEXT_CACHE_CAPACITY), sprite.clone());
+
+        assert_eq!(cache.entries.len(), TEXT_CACHE_CAPACITY);
+        assert!(cache.get(&key(0)).is_some());
+        assert!(cache.get(&key(1)).is_none());
+        assert!(cache.get(&key(2)).is_some());
+        assert!(cache.get(&key(TEXT_CACHE_CAPACITY)).is_some());
+    }
+}
@@ -13,6 +13,17 @@ pub enum CannonType {
     DivergentBullet { a: f64, b: f64 },
 }
 
+impl CannonType {
+    /// The name of the cannon, as shown to the player.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            CannonType::RectBullet => "Rect",
+            CannonType::SineBullet { .. } => "Sine",
+            CannonType::DivergentBullet { .. } => "Divergent",
+        }
+    }
+}
+
 pub trait Bullet {
     /// Update the bullet.
     /// If the bullet should be destroyed, e.g. because it has left the screen,
@@ -20,8 +31,9 @@ pub trait Bullet {
     /// Otherwise, return `Some(update_bullet)`.
     fn update(self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>>;
 
-    /// Render the bullet to the screen.
-    fn render(&self, phi: &mut Phi);
+    /// Render the bullet to the screen, `alpha` of the way between its
+    /// previous and its current position.
+    fn render(&self, phi: &mut Phi, alpha: f64);
 
     /// Return the bullet's bounding box.
     fn rect(&self) -> Rectangle;
@@ -35,22 +47,18 @@ pub fn spawn_bullets(cannon: CannonType,
     match cannon {
         CannonType::RectBullet =>
             vec![
-                Box::new(RectBullet {
-                    rect: Rectangle {
-                        x: cannons_x,
-                        y: cannon1_y,
-                        w: BULLET_W,
-                        h: BULLET_H,
-                    }
-                }),
-                Box::new(RectBullet {
-                    rect: Rectangle {
-                        x: cannons_x,
-                        y: cannon2_y,
-                        w: BULLET_W,
-                        h: BULLET_H,
-                    }
-                }),
+                Box::new(RectBullet::new(Rectangle {
+                    x: cannons_x,
+                    y: cannon1_y,
+                    w: BULLET_W,
+                    h: BULLET_H,
+                })),
+                Box::new(RectBullet::new(Rectangle {
+                    x: cannons_x,
+                    y: cannon2_y,
+                    w: BULLET_W,
+                    h: BULLET_H,
+                })),
             ],
         
         CannonType::SineBullet { amplitude, angular_vel } =>
@@ -61,6 +69,12 @@ pub fn spawn_bullets(cannon: CannonType,
                     amplitude: amplitude,
                     angular_vel: angular_vel,
                     total_time: 0.0,
+                    prev_rect: Rectangle {
+                        x: cannons_x,
+                        y: cannon1_y,
+                        w: BULLET_W,
+                        h: BULLET_H,
+                    },
                 }),
                 Box::new(SineBullet {
                     pos_x: cannons_x,
@@ -68,6 +82,12 @@ pub fn spawn_bullets(cannon: CannonType,
                     amplitude: amplitude,
                     angular_vel: angular_vel,
                     total_time: 0.0,
+                    prev_rect: Rectangle {
+                        x: cannons_x,
+                        y: cannon2_y,
+                        w: BULLET_W,
+                        h: BULLET_H,
+                    },
                 }),
             ],
         
@@ -80,6 +100,12 @@ pub fn spawn_bullets(cannon: CannonType,
                     a: -a,
                     b: b,
                     total_time: 0.0,
+                    prev_rect: Rectangle {
+                        x: cannons_x,
+                        y: cannon1_y,
+                        w: BULLET_W,
+                        h: BULLET_H,
+                    },
                 }),
                 // If a,b > 0, eventually goes downwards
                 Box::new(DivergentBullet {
@@ -88,6 +114,12 @@ pub fn spawn_bullets(cannon: CannonType,
                     a: a,
                     b: b,
                     total_time: 0.0,
+                    prev_rect: Rectangle {
+                        x: cannons_x,
+                        y: cannon2_y,
+                        w: BULLET_W,
+                        h: BULLET_H,
+                    },
                 }),
             ]
     }
@@ -98,11 +130,22 @@ pub fn spawn_bullets(cannon: CannonType,
 #[derive(Clone, Copy)]
 pub struct RectBullet {
     rect: Rectangle,
+    prev_rect: Rectangle,
+}
+
+impl RectBullet {
+    fn new(rect: Rectangle) -> RectBullet {
+        RectBullet {
+            rect: rect,
+            prev_rect: rect,
+        }
+    }
 }
 
 impl Bullet for RectBullet {
     fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
         let (w, _) = phi.output_size();
+        self.prev_rect = self.rect;
         self.rect.x += BULLET_SPEED * dt;
 
         // If the bullet has left the screen, then delete it.
@@ -114,10 +157,12 @@ impl Bullet for RectBullet {
     }
 
     /// Render the bullet to the screen.
-    fn render(&self, phi: &mut Phi) {
+    fn render(&self, phi: &mut Phi, alpha: f64) {
         // We will render this kind of bullet in yellow.
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+        if let Some(rect) = self.prev_rect.lerp(self.rect, alpha).to_sdl() {
+            let _ = phi.renderer.fill_rect(rect);
+        }
     }
 
     /// Return the bullet's bounding box.
@@ -135,10 +180,12 @@ pub struct SineBullet {
     amplitude: f64,
     angular_vel: f64,
     total_time: f64,
+    prev_rect: Rectangle,
 }
 
 impl Bullet for SineBullet {
     fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+        self.prev_rect = self.rect();
         self.total_time += dt;
         self.pos_x += BULLET_SPEED * dt;
 
@@ -152,10 +199,12 @@ impl Bullet for SineBullet {
         }
     }
 
-    fn render(&self, phi: &mut Phi) {
+    fn render(&self, phi: &mut Phi, alpha: f64) {
         // We will render this kind of bullet in yellow.
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+        if let Some(rect) = self.prev_rect.lerp(self.rect(), alpha).to_sdl() {
+            let _ = phi.renderer.fill_rect(rect);
+        }
     }
 
     fn rect(&self) -> Rectangle {
@@ -178,10 +227,12 @@ pub struct DivergentBullet {
     a: f64, // Influences the bump's height
     b: f64, // Influences the bump's width
     total_time: f64,
+    prev_rect: Rectangle,
 }
 
 impl Bullet for DivergentBullet {
     fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+        self.prev_rect = self.rect();
         self.total_time += dt;
         self.pos_x += BULLET_SPEED * dt;
 
@@ -197,10 +248,12 @@ impl Bullet for DivergentBullet {
         }
     }
 
-    fn render(&self, phi: &mut Phi) {
+    fn render(&self, phi: &mut Phi, alpha: f64) {
         // We will render this kind of bullet in yellow.
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+        if let Some(rect) = self.prev_rect.lerp(self.rect(), alpha).to_sdl() {
+            let _ = phi.renderer.fill_rect(rect);
+        }
     }
 
     fn rect(&self) -> Rectangle {
@@ -1,11 +1,16 @@
-use crate::phi::{Phi, View, ViewAction};
+use crate::phi::{Action, Phi, View, ViewAction};
+use crate::phi::audio::SoundSettings;
 use crate::phi::data::{MaybeAlive, Rectangle};
+use crate::phi::error::Result;
 use crate::phi::gfx::{AnimatedSprite, AnimatedSpriteDescr, CopySprite, Sprite};
+use crate::views::high_scores::{HighScores, HIGH_SCORES_PATH};
+use crate::views::hud::Hud;
 use crate::views::shared::BgSet;
 use crate::views::bullets::*;
 use sdl2::pixels::Color;
-use sdl2::mixer::{Chunk, Music};
-use std::path::Path;
+use rand::Rng;
+use sdl2::mixer::Chunk;
+use std::rc::Rc;
 
 const DEBUG: bool = false;
 
@@ -13,6 +18,15 @@ const PLAYER_SPEED: f64 = 180.0;
 const PLAYER_PATH: &'static str = "assets/spaceship.png";
 const PLAYER_W: f64 = 43.0;
 const PLAYER_H: f64 = 39.0;
+const PLAYER_LIVES: u32 = 3;
+
+/// How long, in seconds, the ship stays away after being destroyed. This lets
+/// the explosion play out before it respawns, or before the game ends.
+const RESPAWN_DELAY: f64 = 2.0;
+/// How long, in seconds, asteroids go through the ship once it has respawned.
+const INVULNERABILITY_DURATION: f64 = 2.5;
+/// How many times per second the ship blinks while it is invulnerable.
+const BLINK_RATE: f64 = 8.0;
 
 const ASTEROID_PATH: &'static str = "assets/asteroid.png";
 const ASTEROIDS_WIDE: usize = 21;
@@ -28,6 +42,49 @@ const EXPLOSION_SIDE: f64 = 96.0;
 const EXPLOSION_FPS: f64 = 16.0;
 const EXPLOSION_DURATION: f64 = 1.0 / EXPLOSION_FPS * EXPLOSIONS_TOTAL as f64;
 
+const MUSIC_PATH: &'static str = "assets/mdk_phoenix_orchestral.ogg";
+const BULLET_SOUND_PATH: &'static str = "assets/bullet.ogg";
+const EXPLOSION_SOUND_PATH: &'static str = "assets/explosion.ogg";
+
+/// Explosions tell the player that something happened, and take precedence
+/// over the bullets, of which there are many more.
+const BULLET_SOUND: SoundSettings = SoundSettings { priority: 0, max_instances: 4 };
+const EXPLOSION_SOUND: SoundSettings = SoundSettings { priority: 1, max_instances: 6 };
+/// How long the music takes to fade in when the game starts, and to fade out
+/// when it is over, in seconds.
+const MUSIC_FADE_IN: f64 = 1.0;
+const MUSIC_FADE_OUT: f64 = 2.0;
+
+/// How many points an asteroid moving at `ASTEROID_REFERENCE_SPEED` is worth
+/// when it is shot down. Faster asteroids are harder to hit, and are worth
+/// proportionally more.
+const ASTEROID_POINTS: f64 = 100.0;
+const ASTEROID_REFERENCE_SPEED: f64 = 100.0;
+
+/// What the player achieved during a run, shown once it is over.
+#[derive(Clone, Copy, Default)]
+pub struct RunStats {
+    pub score: u64,
+    /// How long the run lasted, in seconds, not counting the pauses.
+    pub time: f64,
+    pub asteroids_destroyed: u32,
+    pub bullets_fired: u32,
+    pub bullets_hit: u32,
+    /// The seed of the random number generator at the start of the run.
+    pub seed: u64,
+}
+
+impl RunStats {
+    /// The proportion of the bullets which hit an asteroid, in [0, 1].
+    pub fn accuracy(&self) -> f64 {
+        if self.bullets_fired == 0 {
+            0.0
+        } else {
+            self.bullets_hit as f64 / self.bullets_fired as f64
+        }
+    }
+}
+
 /// The different states our ship might be in. In the image, they're ordered
 /// from left to right, then from top to bottom.
 #[derive(Clone, Copy)]
@@ -45,15 +102,21 @@ enum PlayerFrame {
 
 struct Player<'r> {
     rect: Rectangle,
+    /// The bounding box before the last update, used for interpolation.
+    prev_rect: Rectangle,
     sprites: Vec<Sprite<'r>>,
     current: PlayerFrame,
     cannon: CannonType,
+    /// Whether the ship is on the screen, i.e. has not been destroyed.
+    alive: bool,
+    /// How long the ship remains invulnerable, in seconds.
+    invulnerable: f64,
 }
 
 impl<'r> Player<'r> {
-    pub fn new(phi: &mut Phi) -> Player<'r> {
+    pub fn new(phi: &mut Phi) -> Result<Player<'r>> {
         // Get the spaceship's sprites
-        let spritesheet = Sprite::load(&mut phi.renderer, PLAYER_PATH).unwrap();
+        let spritesheet = phi.assets.texture(&phi.renderer, PLAYER_PATH)?;
         let mut sprites = Vec::with_capacity(9);
 
         for y in 0..3 {
@@ -63,39 +126,66 @@ impl<'r> Player<'r> {
                     h: PLAYER_H,
                     x: PLAYER_W * x as f64,
                     y: PLAYER_H * y as f64,
-                }).unwrap());
+                })?);
             }
         }
 
-        Player {
-            // Spawn the player at the center of the screen, vertically.
-            rect: Rectangle {
-                x: 64.0,
-                y: (phi.output_size().1 - PLAYER_H) / 2.0,
-                w: PLAYER_W,
-                h: PLAYER_H,
-            },
+        let rect = Player::spawn_point(phi);
+
+        Ok(Player {
+            rect: rect,
+            prev_rect: rect,
             sprites: sprites,
             current: PlayerFrame::MidNorm,
             cannon: CannonType::RectBullet,
+            alive: true,
+            invulnerable: 0.0,
+        })
+    }
+
+    /// Where the ship appears at the beginning of the game, and whenever it
+    /// respawns: at the center of the screen, vertically.
+    fn spawn_point(phi: &Phi) -> Rectangle {
+        Rectangle {
+            x: 64.0,
+            y: (phi.output_size().1 - PLAYER_H) / 2.0,
+            w: PLAYER_W,
+            h: PLAYER_H,
         }
     }
+
+    /// Brings the ship back at the spawn point after it was destroyed, and
+    /// makes it invulnerable for a while.
+    pub fn respawn(&mut self, phi: &Phi) {
+        self.rect = Player::spawn_point(phi);
+        self.prev_rect = self.rect;
+        self.current = PlayerFrame::MidNorm;
+        self.alive = true;
+        self.invulnerable = INVULNERABILITY_DURATION;
+    }
+
+    /// Whether an asteroid which hits the ship destroys it.
+    pub fn is_vulnerable(&self) -> bool {
+        self.alive && self.invulnerable <= 0.0
+    }
     
     pub fn update(&mut self, phi: &mut Phi, elapsed: f64) {
+        self.invulnerable = (self.invulnerable - elapsed).max(0.0);
+
         // Change the player's cannons
 
-        if phi.events.now.key_1 == Some(true) {
+        if phi.events.now.pressed(Action::Weapon1) {
             self.cannon = CannonType::RectBullet;
         }
 
-        if phi.events.now.key_2 == Some(true) {
+        if phi.events.now.pressed(Action::Weapon2) {
             self.cannon = CannonType::SineBullet {
                 amplitude: 10.0,
                 angular_vel: 15.0,
             };
         }
 
-        if phi.events.now.key_3 == Some(true) {
+        if phi.events.now.pressed(Action::Weapon3) {
             self.cannon = CannonType::DivergentBullet {
                 a: 100.0,
                 b: 1.2,
@@ -104,44 +194,16 @@ impl<'r> Player<'r> {
 
         // Move the player's ship
 
-        let diagonal =
-            (phi.events.key_up ^ phi.events.key_down) &&
-            (phi.events.key_left ^ phi.events.key_right);
-
-        let moved =
-            if diagonal { 0.5f64.sqrt() }
-            else { 1.0 } * PLAYER_SPEED * elapsed;
-
-        let dx = match (phi.events.key_left, phi.events.key_right) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
-        };
-
-        let dy = match (phi.events.key_up, phi.events.key_down) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
-        };
+        // The keyboard moves the ship at full speed, while the stick of a
+        // controller moves it proportionally to how far it is pushed.
+        let (x, y) = phi.events.movement();
+        let dx = x * PLAYER_SPEED * elapsed;
+        let dy = y * PLAYER_SPEED * elapsed;
 
+        self.prev_rect = self.rect;
         self.rect.x += dx;
         self.rect.y += dy;
-
-        // The movable region spans the entire height of the window and 70% of its
-        // width. This way, the player cannot get to the far right of the screen, where
-        // we will spawn the asteroids, and get immediately eliminated.
-        //
-        // We restrain the width because most screens are wider than they are high.
-        let movable_region = Rectangle {
-            x: 0.0,
-            y: 0.0,
-            w: phi.output_size().0 as f64 * 0.70,
-            h: phi.output_size().1 as f64,
-        };
-
-        // If the player cannot fit in the screen, then there is a problem and
-        // the game should be promptly aborted.
-        self.rect = self.rect.move_inside(movable_region).unwrap();
+        self.keep_inside(phi);
 
         // Select the appropriate sprite of the ship to show.
         self.current =
@@ -157,17 +219,54 @@ impl<'r> Player<'r> {
             else { unreachable!() };
     }
     
-    pub fn render(&self, phi: &mut Phi) {
+    /// Moves the ship back into the region where it is allowed to go.
+    pub fn keep_inside(&mut self, phi: &Phi) {
+        // The movable region spans the entire height of the window and 70% of its
+        // width. This way, the player cannot get to the far right of the screen, where
+        // we will spawn the asteroids, and get immediately eliminated.
+        //
+        // We restrain the width because most screens are wider than they are high.
+        let movable_region = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            w: phi.output_size().0 as f64 * 0.70,
+            h: phi.output_size().1 as f64,
+        };
+
+        // If the window was made too small for the ship to fit, we keep it in
+        // the top-left corner until it is large enough again.
+        self.rect = self.rect.move_inside(movable_region)
+            .unwrap_or(Rectangle { x: 0.0, y: 0.0, ..self.rect });
+    }
+
+    /// Called when the window is resized, to keep the ship on the screen.
+    pub fn resize(&mut self, phi: &Phi) {
+        self.keep_inside(phi);
+
+        // Teleport the ship instead of sliding it to its new position.
+        self.prev_rect = self.rect;
+    }
+
+    pub fn render(&self, phi: &mut Phi, alpha: f64) {
+        // Blink while invulnerable, by skipping every other half-period.
+        if !self.alive || (self.invulnerable * BLINK_RATE) as u32 % 2 == 1 {
+            return;
+        }
+
+        let rect = self.prev_rect.lerp(self.rect, alpha);
+
         // Render the bounding box (for debugging purposes)
         if DEBUG {
             phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+            if let Some(rect) = rect.to_sdl() {
+                let _ = phi.renderer.fill_rect(rect);
+            }
         }
 
         // Render the ship's current sprite.
         phi.renderer.copy_sprite(
             &self.sprites[self.current as usize],
-            self.rect);
+            rect);
     }
     
     pub fn spawn_bullets(&self) -> Vec<Box<dyn Bullet>> {
@@ -182,12 +281,13 @@ impl<'r> Player<'r> {
 struct Asteroid<'r> {
     sprite: AnimatedSprite<'r>,
     rect: Rectangle,
+    prev_rect: Rectangle,
     vel: f64,
 }
 
 impl<'r> Asteroid<'r> {
-    fn factory(phi: &mut Phi) -> AsteroidFactory<'r> {
-        AsteroidFactory {
+    fn factory(phi: &mut Phi) -> Result<AsteroidFactory<'r>> {
+        Ok(AsteroidFactory {
             sprite: AnimatedSprite::with_fps(
                 AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
                     image_path: ASTEROID_PATH,
@@ -196,11 +296,12 @@ impl<'r> Asteroid<'r> {
                     frames_wide: ASTEROIDS_WIDE,
                     frame_w: ASTEROID_SIDE,
                     frame_h: ASTEROID_SIDE,
-                }), 1.0),
-        }
+                })?, 1.0),
+        })
     }
     
     fn update(mut self, phi: &mut Phi, dt: f64) -> Option<Asteroid<'r>> {
+        self.prev_rect = self.rect;
         self.rect.x -= dt * self.vel;
         self.sprite.add_time(dt);
 
@@ -211,14 +312,31 @@ impl<'r> Asteroid<'r> {
         }
     }
 
-    fn render(&self, phi: &mut Phi) {
+    /// Called when the window is resized, so that the asteroid stays within
+    /// the screen vertically. It may still be off to its right.
+    fn resize(&mut self, phi: &Phi) {
+        let (_, h) = phi.output_size();
+        self.rect.y = self.rect.y.min(h - ASTEROID_SIDE).max(0.0);
+        self.prev_rect = self.rect;
+    }
+
+    /// How many points the asteroid is worth when it is shot down.
+    fn points(&self) -> u64 {
+        (ASTEROID_POINTS * self.vel / ASTEROID_REFERENCE_SPEED).round() as u64
+    }
+
+    fn render(&self, phi: &mut Phi, alpha: f64) {
+        let rect = self.prev_rect.lerp(self.rect, alpha);
+
         if DEBUG {
             // Render the bounding box
             phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+            if let Some(rect) = rect.to_sdl() {
+                let _ = phi.renderer.fill_rect(rect);
+            }
         }
         
-        phi.renderer.copy_sprite(&self.sprite, self.rect);
+        phi.renderer.copy_sprite(&self.sprite, rect);
     }
     
     fn rect(&self) -> Rectangle {
@@ -236,22 +354,24 @@ impl<'r> AsteroidFactory<'r> {
 
         // FPS in [10.0, 30.0)
         let mut sprite = self.sprite.clone();
-        sprite.set_fps(::rand::random::<f64>().abs() * 20.0 + 10.0);
+        sprite.set_fps(phi.rng.gen::<f64>() * 20.0 + 10.0);
+
+        // In the screen vertically, and over the right of the screen
+        // horizontally.
+        let rect = Rectangle {
+            w: ASTEROID_SIDE,
+            h: ASTEROID_SIDE,
+            x: w,
+            y: phi.rng.gen::<f64>() * (h - ASTEROID_SIDE),
+        };
 
         Asteroid {
             sprite: sprite,
-
-            // In the screen vertically, and over the right of the screen
-            // horizontally.
-            rect: Rectangle {
-                w: ASTEROID_SIDE,
-                h: ASTEROID_SIDE,
-                x: w,
-                y: rand::random::<f64>().abs() * (h - ASTEROID_SIDE),
-            },
+            rect: rect,
+            prev_rect: rect,
 
             // vel in [50.0, 150.0)
-            vel: rand::random::<f64>().abs() * 100.0 + 50.0,
+            vel: phi.rng.gen::<f64>() * 100.0 + 50.0,
         }
     }
 }
@@ -266,8 +386,8 @@ struct Explosion<'r> {
 }
 
 impl<'r> Explosion<'r> {
-    fn factory(phi: &mut Phi) -> ExplosionFactory<'r> {
-        ExplosionFactory {
+    fn factory(phi: &mut Phi) -> Result<ExplosionFactory<'r>> {
+        Ok(ExplosionFactory {
             sprite: AnimatedSprite::with_fps(
                 AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
                     image_path: EXPLOSION_PATH,
@@ -276,8 +396,8 @@ impl<'r> Explosion<'r> {
                     frames_wide: EXPLOSIONS_WIDE,
                     frame_w: EXPLOSION_SIDE,
                     frame_h: EXPLOSION_SIDE,
-                }), EXPLOSION_FPS),
-        }
+                })?, EXPLOSION_FPS),
+        })
     }
     
     fn update(mut self, dt: f64) -> Option<Explosion<'r>> {
@@ -326,93 +446,143 @@ pub struct GameView<'a> {
     explosions: Vec<Explosion<'a>>,
     explosion_factory: ExplosionFactory<'a>,
     bg: BgSet<'a>,
-    music: Music<'a>,
-    bullet_sound: Chunk,
-    explosion_sound: Chunk,
+    /// How many times the ship may still be destroyed, including the current
+    /// one. The game ends when this reaches 0.
+    lives: u32,
+    /// How long until the ship respawns, in seconds, if it was destroyed.
+    respawn_in: f64,
+    stats: RunStats,
+    hud: Hud<'a>,
+    //? Those are `None` when the game runs without audio.
+    bullet_sound: Option<Rc<Chunk>>,
+    explosion_sound: Option<Rc<Chunk>>,
 }
 
 impl<'a> GameView<'a> {
     /*pub fn new(phi: &mut Phi) -> GameView {
-        let bg = BgSet::new(&mut phi.renderer);
+        let bg = BgSet::new(phi);
         GameView::with_backgrounds(phi, bg)
     }*/
     
-    pub fn with_backgrounds(phi: &mut Phi, bg: BgSet) -> GameView<'a> {
-        let music =
-            Music::from_file(Path::new("assets/mdk_phoenix_orchestral.ogg"))
-            .unwrap();
-        
-        music.play(-1).unwrap();
+    pub fn with_backgrounds(phi: &mut Phi, bg: BgSet) -> Result<GameView<'a>> {
+        // Every run gets its own seed, chosen before anything random happens,
+        // so that reseeding with it plays the same run again.
+        let seed = phi.run_seed();
+        phi.reseed(seed);
+
+        if let Some(music) = phi.assets.music(MUSIC_PATH)? {
+            phi.audio.play_music(music, MUSIC_FADE_IN);
+        }
         
-        let bullet_sound =
-            Chunk::from_file(Path::new("assets/bullet.ogg"))
-            .unwrap();
-
-        let explosion_sound =
-            Chunk::from_file(Path::new("assets/explosion.ogg"))
-            .unwrap();
+        let bullet_sound = phi.assets.sound(BULLET_SOUND_PATH)?;
+        let explosion_sound = phi.assets.sound(EXPLOSION_SOUND_PATH)?;
         
-        GameView {
+        Ok(GameView {
             // Entities
-            player: Player::new(phi),
+            player: Player::new(phi)?,
             
             bullets: vec![],
             asteroids: vec![],
-            asteroid_factory: Asteroid::factory(phi),
+            asteroid_factory: Asteroid::factory(phi)?,
             explosions: vec![],
-            explosion_factory: Explosion::factory(phi),
+            explosion_factory: Explosion::factory(phi)?,
             
             // Scenery
             bg: bg,
             
+            // State of the run
+            lives: PLAYER_LIVES,
+            respawn_in: 0.0,
+            stats: RunStats { seed, ..RunStats::default() },
+            hud: Hud::new(),
+            
             // Audio
-            music: music,
             bullet_sound: bullet_sound,
             explosion_sound: explosion_sound,
-        }
+        })
     }
 }
 
 impl<'a> View for GameView<'a> {
-    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> Result<ViewAction> {
         if phi.events.now.quit {
-            return ViewAction::Quit;
+            return Ok(ViewAction::Quit);
         }
         
-        if phi.events.now.key_escape == Some(true) {
+        self.stats.time += elapsed;
+        
+        if phi.events.now.pressed(Action::Back) {
             let bg = self.bg.clone();
-            return ViewAction::Render(Box::new(
-                crate::views::main_menu::MainMenuView::with_backgrounds(phi, bg)))
-            // TODO PauseView
-            //return ViewAction::Render(Box::new(
-            //    crate::views::main_menu::MainMenuView::with_game_state(self)));
+            return Ok(ViewAction::Push(Box::new(
+                crate::views::pause::PauseView::new(phi, bg)?)));
         }
         
-        { // begin reference scope
-        // Take a reference to the content of the box (i.e. the view itself)
-        let game = &mut *self;
+        // Bring the entities back into the window if it was made smaller
+        if phi.events.now.resize.is_some() {
+            self.player.resize(phi);
+
+            for asteroid in &mut self.asteroids {
+                asteroid.resize(phi);
+            }
+        }
         
-        // Update the player
-        game.player.update(phi, elapsed);
+        // Update the player, or bring it back once it has been destroyed for
+        // long enough.
+        if self.player.alive {
+            self.player.update(phi, elapsed);
+        } else {
+            self.respawn_in -= elapsed;
+
+            if self.respawn_in <= 0.0 {
+                if self.lives == 0 {
+                    phi.audio.fade_out_music(MUSIC_FADE_OUT);
+                    let bg = self.bg.clone();
+
+                    // Ask for the player's initials first if the run made it
+                    // into the high-score table. If the table cannot be read,
+                    // we leave it alone rather than replace it by this run.
+                    let scores = match HighScores::load(HIGH_SCORES_PATH) {
+                        Ok(scores) => Some(scores),
+                        Err(err) => {
+                            println!("Could not read the high scores from {}: {}",
+                                HIGH_SCORES_PATH, err);
+                            None
+                        },
+                    };
+
+                    return Ok(ViewAction::Replace(match scores {
+                        Some(scores) if scores.qualifies(self.stats.score) =>
+                            Box::new(crate::views::name_entry::NameEntryView::new(
+                                phi, bg, scores, self.stats)?),
+
+                        _ =>
+                            Box::new(crate::views::game_over::GameOverView::new(
+                                phi, bg, self.stats)?),
+                    }));
+                }
+
+                self.player.respawn(phi);
+            }
+        }
         
         // Update the bullets
-        game.bullets =
-            std::mem::replace(&mut game.bullets, vec![])
+        self.bullets =
+            std::mem::replace(&mut self.bullets, vec![])
             .into_iter()
             .filter_map(|bullet| bullet.update(phi, elapsed))
             .collect();
 
         
         // Update the asteroids
-        game.asteroids =
-            std::mem::replace(&mut game.asteroids, vec![])
+        self.asteroids =
+            std::mem::replace(&mut self.asteroids, vec![])
             .into_iter()
             .filter_map(|asteroid| asteroid.update(phi, elapsed))
             .collect();
         
         // Update the explosions
-        game.explosions =
-            std::mem::replace(&mut game.explosions, vec![])
+        self.explosions =
+            std::mem::replace(&mut self.explosions, vec![])
             .into_iter()
             .filter_map(|explosion| explosion.update(elapsed))
             .collect();
@@ -422,13 +592,13 @@ impl<'a> View for GameView<'a> {
         let mut player_alive = true;
         
         let mut transition_bullets: Vec<_> =
-            std::mem::replace(&mut game.bullets, vec![])
+            std::mem::replace(&mut self.bullets, vec![])
             .into_iter()
             .map(|bullet| MaybeAlive { alive: true, value: bullet })
             .collect();
         
-        game.asteroids =
-            std::mem::replace(&mut game.asteroids, vec![])
+        self.asteroids =
+            std::mem::replace(&mut self.asteroids, vec![])
             .into_iter()
             .filter_map(|asteroid| {
                 // By default, the asteroid has not been in a collision.
@@ -441,9 +611,16 @@ impl<'a> View for GameView<'a> {
                     }
                 }
 
+                // Only the asteroids which were shot down count towards the
+                // score, not those which crashed into the ship.
+                if !asteroid_alive {
+                    self.stats.asteroids_destroyed += 1;
+                    self.stats.score += asteroid.points();
+                }
+
                 // The player's ship is destroyed if it is hit by an asteroid.
                 // In which case, the asteroid is also destroyed.
-                if asteroid.rect().overlaps(game.player.rect) {
+                if self.player.is_vulnerable() && asteroid.rect().overlaps(self.player.rect) {
                     asteroid_alive = false;
                     player_alive = false;
                 }
@@ -451,71 +628,89 @@ impl<'a> View for GameView<'a> {
                 if asteroid_alive {
                     Some(asteroid)
                 } else {
-                    // Spawn an explosive wherever an asteroid was destroyed.
-                    game.explosions.push(
-                        game.explosion_factory.at_center(
-                            asteroid.rect().center()));
+                    // Spawn an explosive wherever an asteroid was destroyed,
+                    // and let it be heard from there.
+                    let center = asteroid.rect().center();
+                    self.explosions.push(self.explosion_factory.at_center(center));
                     
-                    phi.play_sound(&game.explosion_sound);
+                    if let Some(ref sound) = self.explosion_sound {
+                        phi.audio.play_sound_at(sound, center, EXPLOSION_SOUND);
+                    }
                     
                     None
                 }
             })
             .collect();
         
-        game.bullets = transition_bullets.into_iter()
+        self.stats.bullets_hit +=
+            transition_bullets.iter().filter(|bullet| !bullet.alive).count() as u32;
+        
+        self.bullets = transition_bullets.into_iter()
             .filter_map(MaybeAlive::as_option)
             .collect();
         
-        // TODO
-        // For the moment, we won't do anything about the player dying. This will be
-        // the subject of a future episode.
+        // Blow the ship up and take a life away. It respawns after a delay,
+        // unless this was its last life.
         if !player_alive {
-            println!("The player's ship has been destroyed.");
+            let center = self.player.rect.center();
+            self.explosions.push(self.explosion_factory.at_center(center));
+
+            if let Some(ref sound) = self.explosion_sound {
+                phi.audio.play_sound_at(sound, center, EXPLOSION_SOUND);
+            }
+
+            self.player.alive = false;
+            self.lives -= 1;
+            self.respawn_in = RESPAWN_DELAY;
         }
         
         // Allow the player to shoot after the bullets are updated, so that,
         // when rendered for the first time, they are drawn wherever they
         // spawned.
-        if phi.events.now.key_space == Some(true) {
-            game.bullets.append(&mut game.player.spawn_bullets());
-            phi.play_sound(&game.bullet_sound);
+        if self.player.alive && phi.events.now.pressed(Action::Fire) {
+            let mut bullets = self.player.spawn_bullets();
+            self.stats.bullets_fired += bullets.len() as u32;
+            self.bullets.append(&mut bullets);
+            
+            if let Some(ref sound) = self.bullet_sound {
+                phi.audio.play_sound_at(sound, self.player.rect.center(), BULLET_SOUND);
+            }
         }
         
-        // Randomly create an asteroid about once every 100 frames, that is,
+        // Randomly create an asteroid about once every 100 ticks, that is,
         // a bit more often than once every two seconds.
-        if rand::random::<usize>() % 100 == 0 {
-            game.asteroids.push(game.asteroid_factory.random(phi));
+        if phi.rng.gen_range(0, 100) == 0 {
+            self.asteroids.push(self.asteroid_factory.random(phi));
         }
         
         // Update the backgrounds
-        game.bg.back.update(elapsed);
-        game.bg.middle.update(elapsed);
-        game.bg.front.update(elapsed);
-        } // end reference scope
+        self.bg.update(elapsed);
+        
+        // Update the HUD
+        self.hud.update(phi, self.stats.score, self.lives, self.player.cannon)?;
         
-        ViewAction::Render(self)
+        Ok(ViewAction::None)
     }
     
-    fn render(&self, phi: &mut Phi) {
+    fn render(&self, phi: &mut Phi, alpha: f64) {
         // Clear the screen
         phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
         phi.renderer.clear();
         
         // Render the Backgrounds
-        self.bg.back.render(&mut phi.renderer);
-        self.bg.middle.render(&mut phi.renderer);
+        self.bg.back.render(&mut phi.renderer, alpha);
+        self.bg.middle.render(&mut phi.renderer, alpha);
         
         // Render the entities
 
-        self.player.render(phi);
+        self.player.render(phi, alpha);
         
         for bullet in &self.bullets {
-            bullet.render(phi);
+            bullet.render(phi, alpha);
         }
         
         for asteroid in &self.asteroids {
-            asteroid.render(phi);
+            asteroid.render(phi, alpha);
         }
         
         for explosion in &self.explosions {
@@ -523,6 +718,9 @@ impl<'a> View for GameView<'a> {
         }
         
         // Render the foreground
-        self.bg.front.render(&mut phi.renderer);
+        self.bg.front.render(&mut phi.renderer, alpha);
+        
+        // Render the HUD, above everything else
+        self.hud.render(phi);
     }
 }
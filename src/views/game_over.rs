@@ -0,0 +1,99 @@
+use crate::phi::{Phi, View, ViewAction};
+use crate::phi::data::Rectangle;
+use crate::phi::error::Result;
+use crate::phi::gfx::{CopySprite, Sprite};
+use crate::phi::text::{Align, TextBlock};
+use crate::views::game::RunStats;
+use crate::views::shared::{BgSet, Menu, END_OF_RUN_INPUT_DELAY};
+use sdl2::pixels::Color;
+
+
+const FONT: &'static str = "assets/belligerent.ttf";
+
+/// Shown once the player has run out of lives, with a summary of the run.
+pub struct GameOverView<'r> {
+    title: Sprite<'r>,
+    summary: TextBlock<'r>,
+    menu: Menu<'r>,
+    /// How long the player is still ignored, in seconds.
+    input_delay: f64,
+    bg: BgSet<'r>,
+}
+
+impl<'r> GameOverView<'r> {
+    pub fn new(phi: &mut Phi<'_, 'r>, bg: BgSet<'r>, stats: RunStats) -> Result<GameOverView<'r>> {
+        let time = stats.time as u64;
+        let summary = format!(
+            "Score: {}\nTime survived: {}:{:02}\nAsteroids destroyed: {}\nAccuracy: {:.0}%",
+            stats.score, time / 60, time % 60, stats.asteroids_destroyed,
+            stats.accuracy() * 100.0);
+
+        let (win_w, win_h) = phi.output_size();
+
+        Ok(GameOverView {
+            title: phi.ttf_str_sprite("Game Over", FONT, 64, Color::RGB(255, 255, 255))?,
+            summary: phi.layout_text(&summary, FONT, 24, Color::RGB(220, 220, 220),
+                                     None, Align::Center)?,
+            menu: Menu::new(phi, &["Retry", "Main Menu"])?
+                .centered_at((win_w / 2.0, win_h * 0.72)),
+            input_delay: END_OF_RUN_INPUT_DELAY,
+            bg: bg,
+        })
+    }
+}
+
+impl<'r> View for GameOverView<'r> {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> Result<ViewAction> {
+        if phi.events.now.quit {
+            return Ok(ViewAction::Quit);
+        }
+
+        if self.input_delay > 0.0 {
+            self.input_delay -= elapsed;
+            self.bg.update(elapsed);
+            return Ok(ViewAction::None);
+        }
+
+        match self.menu.update(phi) {
+            Some(0) => {
+                let bg = self.bg.clone();
+                return Ok(ViewAction::Replace(Box::new(
+                    crate::views::game::GameView::with_backgrounds(phi, bg)?)));
+            },
+
+            Some(_) => {
+                let bg = self.bg.clone();
+                return Ok(ViewAction::Replace(Box::new(
+                    crate::views::main_menu::MainMenuView::with_backgrounds(phi, bg)?)));
+            },
+
+            None => {},
+        }
+
+        // Keep the backgrounds moving, as they did during the game
+        self.bg.update(elapsed);
+
+        Ok(ViewAction::None)
+    }
+
+    fn render(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.bg.render(&mut phi.renderer, alpha);
+
+        // Render the title and, below it, the summary of the run
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = self.title.size();
+        phi.renderer.copy_sprite(&self.title,
+            Rectangle::with_size(w, h).center_at((win_w / 2.0, win_h * 0.15)));
+
+        let (w, h) = self.summary.size();
+        phi.renderer.copy_sprite(&self.summary,
+            Rectangle::with_size(w, h).center_at((win_w / 2.0, win_h * 0.4)));
+
+        self.menu.render(phi);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::phi::{Action, Phi, View, ViewAction};
+use crate::phi::data::Rectangle;
+use crate::phi::error::Result;
+use crate::phi::gfx::{CopySprite, Sprite};
+use crate::views::shared::BgSet;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+pub const HIGH_SCORES_PATH: &'static str = "highscores.txt";
+
+/// How many runs the table keeps.
+const MAX_ENTRIES: usize = 10;
+
+/// How many letters the player enters as their name.
+pub const INITIALS_LEN: usize = 3;
+
+const FONT: &'static str = "assets/belligerent.ttf";
+
+/// A run which made it into the table.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub initials: String,
+    pub score: u64,
+    /// When the run ended, in seconds since the Unix epoch.
+    pub date: u64,
+    /// The seed of the run, so that it can be played again.
+    pub seed: u64,
+}
+
+impl Entry {
+    pub fn new(initials: String, score: u64, seed: u64) -> Entry {
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or(0);
+
+        Entry { initials, score, date, seed }
+    }
+
+    fn parse(line: &str) -> Option<Entry> {
+        let mut words = line.split_whitespace();
+
+        let entry = Entry {
+            initials: words.next()?.to_string(),
+            score: words.next()?.parse().ok()?,
+            date: words.next()?.parse().ok()?,
+            seed: words.next()?.parse().ok()?,
+        };
+
+        if words.next().is_some()
+            || entry.initials.len() != INITIALS_LEN
+            || !entry.initials.chars().all(|c| c.is_ascii_uppercase()) {
+            return None;
+        }
+
+        Some(entry)
+    }
+}
+
+/// The best runs, from the highest score to the lowest. They are stored in a
+/// text file with one line per run:
+///
+///     # initials score date seed
+///     ABC 12300 1700000000 42
+///
+/// Lines which cannot be read are skipped, so that a damaged file only loses
+/// the runs it damaged.
+pub struct HighScores {
+    entries: Vec<Entry>,
+}
+
+impl HighScores {
+    pub fn new() -> HighScores {
+        HighScores { entries: vec![] }
+    }
+
+    /// Reads the table from the content of a high-score file.
+    pub fn parse(source: &str) -> HighScores {
+        let mut scores = HighScores::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match Entry::parse(line) {
+                Some(entry) => { scores.insert(entry); },
+                None => println!("Skipped line {} of the high scores: `{}`", i + 1, line),
+            }
+        }
+
+        scores
+    }
+
+    /// Reads the table from the file at `path`, starting with an empty one if
+    /// there is none yet. Fails if the file exists but cannot be read, in
+    /// which case it must not be saved over, or every run in it would be lost.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<HighScores> {
+        match fs::read_to_string(path) {
+            Ok(source) => Ok(HighScores::parse(&source)),
+
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound =>
+                Ok(HighScores::new()),
+
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads the table from the file at `path` to show it, starting with an
+    /// empty one if it cannot be read.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> HighScores {
+        HighScores::load(&path).unwrap_or_else(|err| {
+            println!("Could not read the high scores from {}: {}",
+                path.as_ref().display(), err);
+            HighScores::new()
+        })
+    }
+
+    /// Writes the table to the file at `path`. It is first written alongside
+    /// it, then moved in place, so that the previous table is kept whole if
+    /// we fail midway.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+
+        {
+            let mut out = io::BufWriter::new(fs::File::create(&tmp)?);
+            writeln!(out, "# initials score date seed")?;
+
+            for entry in &self.entries {
+                writeln!(out, "{} {} {} {}", entry.initials, entry.score, entry.date, entry.seed)?;
+            }
+
+            out.flush()?;
+        }
+
+        fs::rename(&tmp, path)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Whether a run with the given score would make it into the table.
+    pub fn qualifies(&self, score: u64) -> bool {
+        score > 0 && (self.entries.len() < MAX_ENTRIES
+            || self.entries.last().map_or(true, |last| score > last.score))
+    }
+
+    /// Adds a run to the table and returns its rank, starting at 0, or `None`
+    /// if its score was too low. Among equal scores, older runs come first.
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        let rank = self.entries.iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+/// Formats a number of seconds since the Unix epoch as `YYYY-MM-DD`, in UTC.
+fn format_date(date: u64) -> String {
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (date / 86400) as i64 + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+
+/// Lists the best runs.
+pub struct HighScoresView<'r> {
+    title: Sprite<'r>,
+    rows: Vec<Sprite<'r>>,
+    bg: BgSet<'r>,
+}
+
+impl<'r> HighScoresView<'r> {
+    pub fn new(phi: &mut Phi<'_, 'r>, bg: BgSet<'r>) -> Result<HighScoresView<'r>> {
+        let scores = HighScores::load_or_default(HIGH_SCORES_PATH);
+        let mut rows = Vec::with_capacity(scores.entries().len());
+
+        for (i, entry) in scores.entries().iter().enumerate() {
+            let text = format!("{:>2}. {}  {:>8}  {}  #{}", i + 1, entry.initials,
+                entry.score, format_date(entry.date), entry.seed);
+            rows.push(phi.ttf_str_sprite(&text, FONT, 22, Color::RGB(220, 220, 220))?);
+        }
+
+        if rows.is_empty() {
+            rows.push(phi.ttf_str_sprite("No runs yet", FONT, 22, Color::RGB(220, 220, 220))?);
+        }
+
+        Ok(HighScoresView {
+            title: phi.ttf_str_sprite("High Scores", FONT, 56, Color::RGB(255, 255, 255))?,
+            rows: rows,
+            bg: bg,
+        })
+    }
+}
+
+impl<'r> View for HighScoresView<'r> {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> Result<ViewAction> {
+        if phi.events.now.quit {
+            return Ok(ViewAction::Quit);
+        }
+
+        if phi.events.now.pressed(Action::Back)
+            || phi.events.now.pressed(Action::Confirm)
+            || phi.events.now.mouse_pressed(MouseButton::Left) {
+            let bg = self.bg.clone();
+            return Ok(ViewAction::Replace(Box::new(
+                crate::views::main_menu::MainMenuView::with_backgrounds(phi, bg)?)));
+        }
+
+        // Update the backgrounds
+        self.bg.update(elapsed);
+
+        Ok(ViewAction::None)
+    }
+
+    fn render(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.bg.render(&mut phi.renderer, alpha);
+
+        // Render the title, then the runs one below the other
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = self.title.size();
+        phi.renderer.copy_sprite(&self.title,
+            Rectangle::with_size(w, h).center_at((win_w / 2.0, win_h * 0.12)));
+
+        // The rows are aligned on their left edge, with the widest one
+        // centered on the screen.
+        let rows_w = self.rows.iter().map(|row| row.size().0).fold(0.0, f64::max);
+
+        for (i, row) in self.rows.iter().enumerate() {
+            let (w, h) = row.size();
+            phi.renderer.copy_sprite(row, Rectangle {
+                x: (win_w - rows_w) / 2.0,
+                y: win_h * 0.22 + 40.0 * i as f64,
+                w: w,
+                h: h,
+            });
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(initials: &str, score: u64, date: u64) -> Entry {
+        Entry { initials: initials.to_string(), score, date, seed: 42 }
+    }
+
+    fn scores(table: &HighScores) -> Vec<u64> {
+        table.entries().iter().map(|entry| entry.score).collect()
+    }
+
+    #[test]
+    fn parse_sorts_and_skips_bad_lines() {
+        let table = HighScores::parse("
+            # initials score date seed
+            ABC 100 1700000000 1
+            abc 500 1700000000 2
+            XYZ 300 1700000000 3
+            TOOLONG 400 1700000000 4
+            DEF 200 1700000000
+            GHI 250 1700000000 5 6
+        ");
+
+        assert_eq!(scores(&table), vec![300, 100]);
+        assert_eq!(table.entries()[0].initials, "XYZ");
+        assert_eq!(table.entries()[0].seed, 3);
+    }
+
+    #[test]
+    fn insert_keeps_the_best_runs_in_order() {
+        let mut table = HighScores::new();
+
+        for i in 0..MAX_ENTRIES as u64 {
+            assert_eq!(table.insert(entry("AAA", 100 * (i + 1), i)), Some(0));
+        }
+
+        // Equal scores go after the older runs.
+        assert_eq!(table.insert(entry("BBB", 500, 100)), Some(6));
+        assert_eq!(table.entries()[6].initials, "BBB");
+        assert_eq!(table.entries().len(), MAX_ENTRIES);
+        assert_eq!(*scores(&table).last().unwrap(), 200);
+
+        assert_eq!(table.insert(entry("CCC", 200, 200)), None);
+        assert_eq!(table.insert(entry("CCC", 50, 200)), None);
+    }
+
+    #[test]
+    fn qualifies_once_the_table_is_full_only_above_the_last() {
+        let mut table = HighScores::new();
+        assert!(table.qualifies(1));
+        assert!(!table.qualifies(0));
+
+        for _ in 0..MAX_ENTRIES {
+            table.insert(entry("AAA", 100, 0));
+        }
+
+        assert!(!table.qualifies(100));
+        assert!(table.qualifies(101));
+    }
+
+    #[test]
+    fn format_date_in_utc() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_date(4_102_444_799), "2099-12-31");
+    }
+}
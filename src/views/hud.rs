@@ -0,0 +1,102 @@
+use crate::phi::Phi;
+use crate::phi::data::Rectangle;
+use crate::phi::error::Result;
+use crate::phi::gfx::{CopySprite, Sprite};
+use crate::views::bullets::CannonType;
+use sdl2::pixels::Color;
+
+
+const HUD_FONT: &'static str = "assets/belligerent.ttf";
+const HUD_FONT_SIZE: u16 = 22;
+/// The space between the HUD and the edges of the screen.
+const HUD_MARGIN: f64 = 10.0;
+
+/// A text of the HUD, along with the value it was rendered for, so that it is
+/// only rendered again once the value changes.
+struct Label<'r, T> {
+    value: Option<T>,
+    sprite: Option<Sprite<'r>>,
+}
+
+impl<'r, T: PartialEq> Label<'r, T> {
+    fn new() -> Label<'r, T> {
+        Label {
+            value: None,
+            sprite: None,
+        }
+    }
+
+    fn set<F>(&mut self, phi: &mut Phi<'_, 'r>, value: T, text: F) -> Result<()>
+        where F: FnOnce(&T) -> String
+    {
+        if self.value.as_ref() == Some(&value) {
+            return Ok(());
+        }
+
+        self.sprite = Some(phi.ttf_str_sprite(&text(&value), HUD_FONT, HUD_FONT_SIZE,
+                                              Color::RGB(220, 220, 220))?);
+        self.value = Some(value);
+        Ok(())
+    }
+}
+
+/// Shows the score, the remaining lives and the current cannon on top of the
+/// game.
+pub struct Hud<'r> {
+    score: Label<'r, u64>,
+    lives: Label<'r, u32>,
+    cannon: Label<'r, &'static str>,
+}
+
+impl<'r> Hud<'r> {
+    pub fn new() -> Hud<'r> {
+        Hud {
+            score: Label::new(),
+            lives: Label::new(),
+            cannon: Label::new(),
+        }
+    }
+
+    /// Called once per tick with the current state of the run.
+    pub fn update(&mut self, phi: &mut Phi<'_, 'r>, score: u64, lives: u32, cannon: CannonType) -> Result<()> {
+        self.score.set(phi, score, |score| format!("Score: {}", score))?;
+        self.lives.set(phi, lives, |lives| format!("Lives: {}", lives))?;
+        self.cannon.set(phi, cannon.name(), |name| format!("Cannon: {}", name))
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        let (win_w, _) = phi.output_size();
+
+        // The score goes in the top-left corner, the lives in the middle and
+        // the cannon in the top-right corner.
+        if let Some(ref sprite) = self.score.sprite {
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite, Rectangle {
+                x: HUD_MARGIN,
+                y: HUD_MARGIN,
+                w: w,
+                h: h,
+            });
+        }
+
+        if let Some(ref sprite) = self.lives.sprite {
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite, Rectangle {
+                x: (win_w - w) / 2.0,
+                y: HUD_MARGIN,
+                w: w,
+                h: h,
+            });
+        }
+
+        if let Some(ref sprite) = self.cannon.sprite {
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite, Rectangle {
+                x: win_w - w - HUD_MARGIN,
+                y: HUD_MARGIN,
+                w: w,
+                h: h,
+            });
+        }
+    }
+}
@@ -1,4 +1,10 @@
 pub mod bullets;
 pub mod game;
+pub mod game_over;
+pub mod high_scores;
+pub mod hud;
 pub mod main_menu;
+pub mod name_entry;
+pub mod pause;
+pub mod settings;
 pub mod shared;
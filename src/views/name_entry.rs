@@ -0,0 +1,156 @@
+use crate::phi::{Action, Phi, View, ViewAction};
+use crate::phi::data::Rectangle;
+use crate::phi::error::Result;
+use crate::phi::gfx::{CopySprite, Sprite};
+use crate::views::game::RunStats;
+use crate::views::high_scores::{Entry, HighScores, HIGH_SCORES_PATH, INITIALS_LEN};
+use crate::views::shared::{BgSet, END_OF_RUN_INPUT_DELAY};
+use sdl2::pixels::Color;
+
+
+const FONT: &'static str = "assets/belligerent.ttf";
+const LETTERS: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// The horizontal distance between the centers of two letters.
+const LETTER_SPACING: f64 = 70.0;
+
+/// Asks the player for their initials after a run which made it into the
+/// high-score table. Up and down change the selected letter, left and right
+/// move between letters, and confirming on the last letter saves the run.
+pub struct NameEntryView<'r> {
+    title: Sprite<'r>,
+    prompt: Sprite<'r>,
+    /// Every letter, as drawn when it is not selected, then when it is.
+    idle_letters: Vec<Sprite<'r>>,
+    hover_letters: Vec<Sprite<'r>>,
+    /// The index in `LETTERS` of every letter of the initials.
+    initials: [usize; INITIALS_LEN],
+    cursor: usize,
+    scores: HighScores,
+    stats: RunStats,
+    /// How long the player is still ignored, in seconds.
+    input_delay: f64,
+    bg: BgSet<'r>,
+}
+
+impl<'r> NameEntryView<'r> {
+    pub fn new(phi: &mut Phi<'_, 'r>, bg: BgSet<'r>, scores: HighScores, stats: RunStats) -> Result<NameEntryView<'r>> {
+        let mut idle_letters = Vec::with_capacity(LETTERS.len());
+        let mut hover_letters = Vec::with_capacity(LETTERS.len());
+
+        for letter in LETTERS.chars() {
+            let letter = letter.to_string();
+            idle_letters.push(phi.ttf_str_sprite(&letter, FONT, 56, Color::RGB(220, 220, 220))?);
+            hover_letters.push(phi.ttf_str_sprite(&letter, FONT, 72, Color::RGB(255, 220, 80))?);
+        }
+
+        Ok(NameEntryView {
+            title: phi.ttf_str_sprite("New High Score!", FONT, 56, Color::RGB(255, 255, 255))?,
+            prompt: phi.ttf_str_sprite("Enter your initials", FONT, 28, Color::RGB(220, 220, 220))?,
+            idle_letters: idle_letters,
+            hover_letters: hover_letters,
+            initials: [0; INITIALS_LEN],
+            cursor: 0,
+            scores: scores,
+            stats: stats,
+            input_delay: END_OF_RUN_INPUT_DELAY,
+            bg: bg,
+        })
+    }
+
+    /// Adds the run to the table and writes it to disk. Failing to do so is
+    /// not worth interrupting the game for.
+    fn save(&mut self) {
+        let initials = self.initials.iter()
+            .map(|&i| LETTERS.as_bytes()[i] as char)
+            .collect();
+
+        self.scores.insert(Entry::new(initials, self.stats.score, self.stats.seed));
+
+        if let Err(err) = self.scores.save(HIGH_SCORES_PATH) {
+            println!("Could not save the high scores to {}: {}", HIGH_SCORES_PATH, err);
+        }
+    }
+}
+
+impl<'r> View for NameEntryView<'r> {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> Result<ViewAction> {
+        if phi.events.now.quit {
+            return Ok(ViewAction::Quit);
+        }
+
+        if self.input_delay > 0.0 {
+            self.input_delay -= elapsed;
+            self.bg.update(elapsed);
+            return Ok(ViewAction::None);
+        }
+
+        let letter = &mut self.initials[self.cursor];
+
+        if phi.events.now.pressed(Action::MoveUp) {
+            *letter = (*letter + LETTERS.len() - 1) % LETTERS.len();
+        }
+
+        if phi.events.now.pressed(Action::MoveDown) {
+            *letter = (*letter + 1) % LETTERS.len();
+        }
+
+        if phi.events.now.pressed(Action::MoveLeft) || phi.events.now.pressed(Action::Back) {
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+
+        if phi.events.now.pressed(Action::MoveRight) {
+            self.cursor = (self.cursor + 1).min(INITIALS_LEN - 1);
+        }
+
+        if phi.events.now.pressed(Action::Confirm) {
+            if self.cursor + 1 < INITIALS_LEN {
+                self.cursor += 1;
+            } else {
+                self.save();
+
+                let bg = self.bg.clone();
+                return Ok(ViewAction::Replace(Box::new(
+                    crate::views::game_over::GameOverView::new(phi, bg, self.stats)?)));
+            }
+        }
+
+        // Update the backgrounds
+        self.bg.update(elapsed);
+
+        Ok(ViewAction::None)
+    }
+
+    fn render(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.bg.render(&mut phi.renderer, alpha);
+
+        // Render the title and the prompt
+        let (win_w, win_h) = phi.output_size();
+
+        let (w, h) = self.title.size();
+        phi.renderer.copy_sprite(&self.title,
+            Rectangle::with_size(w, h).center_at((win_w / 2.0, win_h * 0.2)));
+
+        let (w, h) = self.prompt.size();
+        phi.renderer.copy_sprite(&self.prompt,
+            Rectangle::with_size(w, h).center_at((win_w / 2.0, win_h * 0.35)));
+
+        // Render the initials, the selected letter standing out
+        let first_x = win_w / 2.0 - LETTER_SPACING * (INITIALS_LEN - 1) as f64 / 2.0;
+
+        for (i, &letter) in self.initials.iter().enumerate() {
+            let sprite =
+                if i == self.cursor { &self.hover_letters[letter] }
+                else { &self.idle_letters[letter] };
+
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite,
+                Rectangle::with_size(w, h)
+                .center_at((first_x + LETTER_SPACING * i as f64, win_h * 0.55)));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::phi::{Action, Phi, View, ViewAction};
+use crate::phi::data::Rectangle;
+use crate::phi::error::Result;
+use crate::views::shared::{BgSet, Menu};
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+
+/// Shown on top of the game when the player presses Escape. The game below
+/// is not updated, and thus frozen, until the player resumes it.
+pub struct PauseView<'r> {
+    menu: Menu<'r>,
+    //? The backgrounds of the paused game, so that we can start a new one
+    //? or go back to the main menu without them jumping around.
+    bg: BgSet<'r>,
+}
+
+impl<'r> PauseView<'r> {
+    pub fn new(phi: &mut Phi<'_, 'r>, bg: BgSet<'r>) -> Result<PauseView<'r>> {
+        // Freeze the music and the sound effects along with the game.
+        phi.audio.pause();
+
+        Ok(PauseView {
+            menu: Menu::new(phi, &["Resume", "Restart", "Quit to Menu"])?,
+            bg: bg,
+        })
+    }
+
+    /// Goes back to the game, exactly where the player left it.
+    fn resume(&self, phi: &mut Phi) -> ViewAction {
+        phi.audio.resume();
+        ViewAction::Pop
+    }
+}
+
+impl<'r> View for PauseView<'r> {
+    fn update(&mut self, phi: &mut Phi, _: f64) -> Result<ViewAction> {
+        if phi.events.now.quit {
+            return Ok(ViewAction::Quit);
+        }
+
+        if phi.events.now.pressed(Action::Back) {
+            return Ok(self.resume(phi));
+        }
+
+        Ok(match self.menu.update(phi) {
+            Some(0) => self.resume(phi),
+
+            Some(1) => {
+                // The new game starts its own music.
+                phi.audio.stop();
+
+                let bg = self.bg.clone();
+                ViewAction::ReplaceAll(Box::new(
+                    crate::views::game::GameView::with_backgrounds(phi, bg)?))
+            },
+
+            Some(_) => {
+                phi.audio.stop();
+
+                let bg = self.bg.clone();
+                ViewAction::ReplaceAll(Box::new(
+                    crate::views::main_menu::MainMenuView::with_backgrounds(phi, bg)?))
+            },
+
+            None => ViewAction::None,
+        })
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Darken the game, which has already been rendered below us.
+        let (win_w, win_h) = phi.output_size();
+        phi.renderer.set_blend_mode(BlendMode::Blend);
+        phi.renderer.set_draw_color(Color::RGBA(0, 0, 0, 150));
+        if let Some(rect) = Rectangle::with_size(win_w, win_h).to_sdl() {
+            let _ = phi.renderer.fill_rect(rect);
+        }
+        phi.renderer.set_blend_mode(BlendMode::None);
+
+        self.menu.render(phi);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,211 @@
+use crate::phi::{Action, Phi, View, ViewAction};
+use crate::phi::display::{DisplayMode, Resolution};
+use crate::phi::error::Result;
+use crate::phi::settings::Settings;
+use crate::views::shared::{BgSet, Menu};
+use sdl2::pixels::Color;
+
+
+/// The frame rates the player may cap the game at, 0 meaning no limit.
+const FPS_LIMITS: &'static [u32] = &[30, 60, 120, 144, 240, 0];
+
+/// How many times the player presses right to go from silence to full volume.
+const VOLUME_STEPS: f64 = 10.0;
+
+const ENTRY_MUSIC_VOLUME: usize = 0;
+const ENTRY_SFX_VOLUME: usize = 1;
+const ENTRY_DISPLAY_MODE: usize = 2;
+const ENTRY_RESOLUTION: usize = 3;
+const ENTRY_VSYNC: usize = 4;
+const ENTRY_MAX_FPS: usize = 5;
+const ENTRY_SHOW_FPS: usize = 6;
+const ENTRY_BACK: usize = 7;
+
+/// Lets the player change the settings. Left and right change the selected
+/// one, which takes effect at once; confirming toggles it. The settings are
+/// saved when leaving.
+pub struct SettingsView<'r> {
+    menu: Menu<'r>,
+    /// The resolutions the player may choose for exclusive fullscreen, with
+    /// `None` standing for that of the desktop.
+    resolutions: Vec<Option<Resolution>>,
+    bg: BgSet<'r>,
+}
+
+impl<'r> SettingsView<'r> {
+    pub fn new(phi: &mut Phi<'_, 'r>, bg: BgSet<'r>) -> Result<SettingsView<'r>> {
+        let resolutions = Some(None).into_iter()
+            .chain(phi.resolutions().into_iter().map(Some))
+            .collect();
+
+        Ok(SettingsView {
+            menu: SettingsView::menu(phi, 0)?,
+            resolutions: resolutions,
+            bg: bg,
+        })
+    }
+
+    /// Builds the menu, whose labels hold the current value of the settings.
+    fn menu(phi: &mut Phi<'_, 'r>, selected: usize) -> Result<Menu<'r>> {
+        let settings = phi.settings().clone();
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+
+        let labels = [
+            format!("Music Volume: {:.0}%", settings.music_volume * 100.0),
+            format!("Effects Volume: {:.0}%", settings.sfx_volume * 100.0),
+            format!("Display: {}", match settings.display_mode {
+                DisplayMode::Windowed => "Windowed",
+                DisplayMode::Borderless => "Borderless",
+                DisplayMode::Exclusive => "Fullscreen",
+            }),
+            match settings.resolution {
+                Some(resolution) => format!("Resolution: {}x{} {}Hz",
+                    resolution.w, resolution.h, resolution.refresh_rate),
+                None => "Resolution: Desktop".to_string(),
+            },
+            format!("VSync: {} (on restart)", on_off(settings.vsync)),
+            match settings.max_fps {
+                0 => "FPS Limit: None".to_string(),
+                fps => format!("FPS Limit: {}", fps),
+            },
+            format!("Show FPS and Seed: {}", on_off(settings.show_fps)),
+            "Back".to_string(),
+        ];
+
+        let labels: Vec<&str> = labels.iter().map(|label| label.as_str()).collect();
+        Ok(Menu::new(phi, &labels)?.select(selected))
+    }
+
+    /// Changes the setting of the given entry one step in the given
+    /// direction, i.e. -1 or 1.
+    fn change(&self, settings: &mut Settings, entry: usize, direction: i32) {
+        match entry {
+            ENTRY_MUSIC_VOLUME =>
+                settings.music_volume = step_volume(settings.music_volume, direction),
+            ENTRY_SFX_VOLUME =>
+                settings.sfx_volume = step_volume(settings.sfx_volume, direction),
+            ENTRY_DISPLAY_MODE => {
+                settings.display_mode = cycle(DisplayMode::ALL, settings.display_mode, direction);
+            },
+            ENTRY_RESOLUTION => {
+                settings.resolution = cycle(&self.resolutions, settings.resolution, direction);
+            },
+            ENTRY_VSYNC =>
+                settings.vsync = !settings.vsync,
+            ENTRY_MAX_FPS => {
+                settings.max_fps = cycle(FPS_LIMITS, settings.max_fps, direction);
+            },
+            ENTRY_SHOW_FPS =>
+                settings.show_fps = !settings.show_fps,
+            _ => {},
+        }
+    }
+
+    /// Saves the settings, then goes back to the main menu.
+    fn leave(&self, phi: &mut Phi) -> Result<ViewAction> {
+        if let Err(err) = phi.save_settings() {
+            println!("Could not save the settings: {}", err);
+        }
+
+        let bg = self.bg.clone();
+        Ok(ViewAction::Replace(Box::new(
+            crate::views::main_menu::MainMenuView::with_backgrounds(phi, bg)?)))
+    }
+}
+
+impl<'r> View for SettingsView<'r> {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> Result<ViewAction> {
+        if phi.events.now.quit {
+            return Ok(ViewAction::Quit);
+        }
+
+        if phi.events.now.pressed(Action::Back) {
+            return self.leave(phi);
+        }
+
+        // The entry may have been clicked, in which case it is not the one
+        // which was selected until now.
+        let change = match self.menu.update(phi) {
+            Some(ENTRY_BACK) => return self.leave(phi),
+            Some(entry) => Some((entry, 1)),
+            None if phi.events.now.pressed(Action::MoveLeft) => Some((self.menu.selected(), -1)),
+            None if phi.events.now.pressed(Action::MoveRight) => Some((self.menu.selected(), 1)),
+            None => None,
+        };
+
+        if let Some((entry, direction)) = change {
+            let mut settings = phi.settings().clone();
+            self.change(&mut settings, entry, direction);
+
+            if settings != *phi.settings() {
+                phi.set_settings(settings);
+                self.menu = SettingsView::menu(phi, entry)?;
+            }
+        }
+
+        // Update the backgrounds
+        self.bg.update(elapsed);
+
+        Ok(ViewAction::None)
+    }
+
+    fn render(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.bg.render(&mut phi.renderer, alpha);
+
+        // Render the menu
+        self.menu.render(phi);
+    }
+}
+
+/// The value `direction` steps away from `current` in `values`, wrapping
+/// around at both ends. Values which are not listed go back to the first.
+fn cycle<T: Copy + PartialEq>(values: &[T], current: T, direction: i32) -> T {
+    match values.iter().position(|&value| value == current) {
+        Some(i) => {
+            let next = (i as i32 + direction).rem_euclid(values.len() as i32);
+            values[next as usize]
+        },
+        None => values[0],
+    }
+}
+
+/// Raises or lowers a volume by one step, keeping it in [0, 1].
+fn step_volume(volume: f64, direction: i32) -> f64 {
+    // Round to the step, so that repeated changes do not drift. Dividing
+    // rather than multiplying by the size of a step gives the closest value
+    // to e.g. 0.7, which is then saved as such.
+    let steps = (volume * VOLUME_STEPS).round() + direction as f64;
+    steps.max(0.0).min(VOLUME_STEPS) / VOLUME_STEPS
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_volume_stays_on_round_steps() {
+        let mut volume = 1.0;
+
+        for _ in 0..3 {
+            volume = step_volume(volume, -1);
+        }
+
+        assert_eq!(volume.to_string(), "0.7");
+        assert_eq!(step_volume(0.0, -1), 0.0);
+        assert_eq!(step_volume(1.0, 1), 1.0);
+        assert_eq!(step_volume(0.33, 1), 0.4);
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        assert_eq!(cycle(FPS_LIMITS, 0, 1), 30);
+        assert_eq!(cycle(FPS_LIMITS, 30, -1), 0);
+        assert_eq!(cycle(FPS_LIMITS, 75, 1), 30);
+    }
+}
@@ -1,10 +1,24 @@
+use crate::phi::{Action, Phi};
+use crate::phi::error::Result;
 use crate::phi::data::Rectangle;
 use crate::phi::gfx::{CopySprite, Sprite};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
 use sdl2::render::WindowCanvas;
 
+
+const MENU_FONT: &'static str = "assets/belligerent.ttf";
+
+/// How long, in seconds, the views shown at the end of a run ignore the
+/// player. Confirming shares its keys with firing, so that a player who is
+/// still shooting would otherwise pick whatever is selected.
+pub const END_OF_RUN_INPUT_DELAY: f64 = 1.0;
+
 #[derive(Clone)]
 pub struct Background<'r> {
     pub pos: f64,
+    /// The position before the last update, used for interpolation.
+    pub prev_pos: f64,
     pub vel: f64,
     pub sprite: Sprite<'r>,
 }
@@ -16,23 +30,27 @@ impl<'r> Background<'r> {
         // We define a logical position as depending solely on the time and the
         // dimensions of the image, not on the screen's size.
         let size = self.sprite.size();
+        self.prev_pos = self.pos;
         self.pos += self.vel * elapsed;
         if self.pos > size.0 {
             self.pos -= size.0;
+            self.prev_pos -= size.0;
         }
     }
 
     /// Render the background at its current position, and as many times as
-    /// required to fill the screen.
-    pub fn render(&self, renderer: &mut WindowCanvas) {
+    /// required to fill the screen. `alpha` is the interpolation factor
+    /// between the previous and the current position.
+    pub fn render(&self, renderer: &mut WindowCanvas, alpha: f64) {
         // We determine the scale ratio of the window to the sprite.
         let size = self.sprite.size();
-        let (win_w, win_h) = renderer.output_size().unwrap();
+        let (win_w, win_h) = renderer.logical_size();
         let scale = win_h as f64 / size.1;
 
         // We render as many copies of the background as necessary to fill
         // the screen.
-        let mut physical_left = -self.pos * scale;
+        let pos = self.prev_pos + (self.pos - self.prev_pos) * alpha;
+        let mut physical_left = -pos * scale;
 
         while physical_left < win_w as f64 {
             renderer.copy_sprite(&self.sprite, Rectangle {
@@ -55,23 +73,196 @@ pub struct BgSet<'r> {
 }
 
 impl<'r> BgSet<'r> {
-    pub fn new(renderer: &mut WindowCanvas) -> BgSet<'r> {
-        BgSet {
+    pub fn new(phi: &mut Phi) -> Result<BgSet<'r>> {
+        Ok(BgSet {
             back: Background {
                 pos: 0.0,
+                prev_pos: 0.0,
                 vel: 20.0,
-                sprite: Sprite::load(renderer, "assets/starBG.png").unwrap(),
+                sprite: phi.assets.texture(&phi.renderer, "assets/starBG.png")?,
             },
             middle: Background {
                 pos: 0.0,
+                prev_pos: 0.0,
                 vel: 40.0,
-                sprite: Sprite::load(renderer, "assets/starMG.png").unwrap(),
+                sprite: phi.assets.texture(&phi.renderer, "assets/starMG.png")?,
             },
             front: Background {
                 pos: 0.0,
+                prev_pos: 0.0,
                 vel: 80.0,
-                sprite: Sprite::load(renderer, "assets/starFG.png").unwrap(),
+                sprite: phi.assets.texture(&phi.renderer, "assets/starFG.png")?,
             },
+        })
+    }
+
+    /// Moves every layer, each at its own speed.
+    pub fn update(&mut self, elapsed: f64) {
+        self.back.update(elapsed);
+        self.middle.update(elapsed);
+        self.front.update(elapsed);
+    }
+
+    /// Renders every layer, from the farthest to the nearest. Views which draw
+    /// between the layers render them one by one instead.
+    pub fn render(&self, renderer: &mut WindowCanvas, alpha: f64) {
+        self.back.render(renderer, alpha);
+        self.middle.render(renderer, alpha);
+        self.front.render(renderer, alpha);
+    }
+}
+
+struct MenuEntry<'r> {
+    idle_sprite: Sprite<'r>,
+    hover_sprite: Sprite<'r>,
+}
+
+/// A box holding a column of labels, one of which is selected. The selection
+/// can be changed with the keyboard, a controller or the mouse.
+pub struct Menu<'r> {
+    entries: Vec<MenuEntry<'r>>,
+    selected: usize,
+    /// Where the center of the menu is drawn, or `None` for the center of
+    /// the screen.
+    center: Option<(f64, f64)>,
+}
+
+impl<'r> Menu<'r> {
+    pub fn new(phi: &mut Phi<'_, 'r>, labels: &[&str]) -> Result<Menu<'r>> {
+        let mut entries = Vec::with_capacity(labels.len());
+
+        for label in labels {
+            entries.push(MenuEntry {
+                idle_sprite: phi.ttf_str_sprite(label, MENU_FONT, 32, Color::RGB(220, 220, 220))?,
+                hover_sprite: phi.ttf_str_sprite(label, MENU_FONT, 42, Color::RGB(255, 255, 255))?,
+            });
+        }
+
+        Ok(Menu {
+            entries: entries,
+            selected: 0,
+            center: None,
+        })
+    }
+
+    /// Draws the menu centered on `center` rather than on the screen, e.g. to
+    /// leave room for some text above it.
+    pub fn centered_at(mut self, center: (f64, f64)) -> Menu<'r> {
+        self.center = Some(center);
+        self
+    }
+
+    fn center(&self, phi: &Phi) -> (f64, f64) {
+        let (win_w, win_h) = phi.output_size();
+        self.center.unwrap_or((win_w / 2.0, win_h / 2.0))
+    }
+
+    /// The index of the selected entry.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects the `i`th entry, e.g. to keep the selection when the menu is
+    /// rebuilt with new labels.
+    pub fn select(mut self, i: usize) -> Menu<'r> {
+        self.selected = i.min(self.entries.len() - 1);
+        self
+    }
+
+    /// Moves the selection according to the user's input. Returns the index
+    /// of the entry which was activated, if any.
+    pub fn update(&mut self, phi: &Phi) -> Option<usize> {
+        // Highlight the entry under the pointer when the mouse moves
+        let hovered = self.hovered(phi);
+
+        if phi.events.now.mouse_moved {
+            if let Some(i) = hovered {
+                self.selected = i;
+            }
+        }
+
+        // Activate the currently selected entry if requested, or the one which
+        // was clicked.
+        if phi.events.now.mouse_pressed(MouseButton::Left) && hovered.is_some() {
+            self.selected = hovered.unwrap();
+            return Some(self.selected);
+        }
+
+        if phi.events.now.pressed(Action::Confirm) {
+            return Some(self.selected);
+        }
+
+        // Change the selected entry using the keyboard
+        if phi.events.now.pressed(Action::MoveUp) {
+            self.selected = (self.selected + self.entries.len() - 1) % self.entries.len();
+        }
+
+        if phi.events.now.pressed(Action::MoveDown) {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+
+        None
+    }
+
+    /// Returns the region of the screen covered by the label of the `i`th
+    /// entry, as it is currently drawn.
+    fn label_rect(&self, phi: &Phi, i: usize) -> Rectangle {
+        let (center_x, center_y) = self.center(phi);
+        let label_h = 50.0;
+        let box_h = self.entries.len() as f64 * label_h;
+
+        let sprite =
+            if self.selected == i { &self.entries[i].hover_sprite }
+            else { &self.entries[i].idle_sprite };
+
+        let (w, h) = sprite.size();
+        Rectangle::with_size(w, h)
+            .center_at((center_x, center_y + (label_h - box_h) / 2.0 + label_h * i as f64))
+    }
+
+    /// Returns the index of the entry whose label is under the pointer.
+    fn hovered(&self, phi: &Phi) -> Option<usize> {
+        let (x, y) = phi.events.mouse_pos();
+        let pointer = Rectangle { x: x, y: y, w: 0.0, h: 0.0 };
+
+        (0..self.entries.len()).find(|&i| self.label_rect(phi, i).contains(pointer))
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        // Definitions for the menu's layout
+        let center = self.center(phi);
+        let label_h = 50.0;
+        let border_width = 3.0;
+        let box_w = 360.0;
+        let box_h = self.entries.len() as f64 * label_h;
+        let margin_h = 10.0;
+
+        // Render the border of the colored box which holds the labels
+        phi.renderer.set_draw_color(Color::RGB(70, 15, 70));
+        let border = Rectangle::with_size(box_w + border_width * 2.0, box_h + border_width * 2.0 + margin_h * 2.0)
+            .center_at(center);
+
+        if let Some(rect) = border.to_sdl() {
+            let _ = phi.renderer.fill_rect(rect);
+        }
+
+        // Render the colored box which holds the labels
+        phi.renderer.set_draw_color(Color::RGB(140, 30, 140));
+        let inside = Rectangle::with_size(box_w, box_h + margin_h * 2.0).center_at(center);
+
+        if let Some(rect) = inside.to_sdl() {
+            let _ = phi.renderer.fill_rect(rect);
+        }
+
+        // Render the labels in the menu
+        for (i, entry) in self.entries.iter().enumerate() {
+            let rect = self.label_rect(phi, i);
+
+            if self.selected == i {
+                phi.renderer.copy_sprite(&entry.hover_sprite, rect);
+            } else {
+                phi.renderer.copy_sprite(&entry.idle_sprite, rect);
+            }
         }
     }
 }